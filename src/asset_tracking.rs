@@ -2,7 +2,11 @@
 
 use std::collections::VecDeque;
 
-use bevy::prelude::*;
+use bevy::{
+    asset::{LoadState, RecursiveDependencyLoadState, UntypedAssetId},
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.init_resource::<ResourceHandles>();
@@ -14,51 +18,264 @@ pub trait LoadResource {
     /// have been loaded, it will be inserted as a resource. This ensures that the resource only
     /// exists when the assets are ready.
     fn load_resource<T: Resource + Asset + Clone + FromWorld>(&mut self) -> &mut Self;
+
+    /// Like [`LoadResource::load_resource`], but loading only starts once the `group` is
+    /// requested with [`LoadResourceGroup`].
+    fn load_resource_in_group<T: Resource + Asset + Clone + FromWorld>(
+        &mut self,
+        group: ResourceGroup,
+    ) -> &mut Self;
+
+    /// Makes the loading screen wait for assets that are kept somewhere other than in a resource
+    /// of their own, like the tracks of a playlist. They are part of [`ResourceGroup::STARTUP`].
+    fn track_assets(&mut self, handles: impl IntoIterator<Item = UntypedHandle>) -> &mut Self;
 }
 
 impl LoadResource for App {
     fn load_resource<T: Resource + Asset + Clone + FromWorld>(&mut self) -> &mut Self {
+        self.load_resource_in_group::<T>(ResourceGroup::STARTUP)
+    }
+
+    fn load_resource_in_group<T: Resource + Asset + Clone + FromWorld>(
+        &mut self,
+        group: ResourceGroup,
+    ) -> &mut Self {
         self.init_asset::<T>();
         let world = self.world_mut();
-        let value = T::from_world(world);
-        let assets = world.resource::<AssetServer>();
-        let handle = assets.add(value);
-        let mut handles = world.resource_mut::<ResourceHandles>();
-        handles
-            .waiting
-            .push_back((handle.untyped(), |world, handle| {
-                let assets = world.resource::<Assets<T>>();
-                if let Some(value) = assets.get(handle.id().typed::<T>()) {
-                    world.insert_resource(value.clone());
-                }
-            }));
+        if world
+            .resource::<ResourceHandles>()
+            .requested
+            .contains(&group)
+        {
+            let waiting = start_loading::<T>(world, group);
+            world
+                .resource_mut::<ResourceHandles>()
+                .waiting
+                .push_back(waiting);
+        } else {
+            world
+                .resource_mut::<ResourceHandles>()
+                .pending
+                .entry(group)
+                .or_default()
+                .push(start_loading::<T>);
+        }
+        self
+    }
+
+    fn track_assets(&mut self, handles: impl IntoIterator<Item = UntypedHandle>) -> &mut Self {
+        let mut resource_handles = self.world_mut().resource_mut::<ResourceHandles>();
+        for handle in handles {
+            resource_handles.waiting.push_back(WaitingResource {
+                dependencies: vec![handle.id()],
+                handle,
+                group: ResourceGroup::STARTUP,
+                // There is no resource to insert.
+                insert: |_, _| {},
+            });
+        }
         self
     }
 }
 
+/// A set of resources that are loaded together, e.g. everything a screen needs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ResourceGroup(pub &'static str);
+
+impl ResourceGroup {
+    /// Resources in this group start loading as soon as they are registered.
+    pub const STARTUP: Self = Self("startup");
+}
+
+/// A [`Command`] that starts loading all resources in a [`ResourceGroup`]. Requesting a group
+/// more than once has no effect.
+pub struct LoadResourceGroup(pub ResourceGroup);
+
+impl Command for LoadResourceGroup {
+    fn apply(self, world: &mut World) {
+        world.resource_scope(|world, mut resource_handles: Mut<ResourceHandles>| {
+            if !resource_handles.requested.insert(self.0) {
+                return;
+            }
+            for start in resource_handles.pending.remove(&self.0).unwrap_or_default() {
+                let waiting = start(world, self.0);
+                resource_handles.waiting.push_back(waiting);
+            }
+        });
+    }
+}
+
+/// A run condition that is true once every requested resource has been inserted.
+pub fn all_resources_loaded(resource_handles: Res<ResourceHandles>) -> bool {
+    resource_handles.is_all_loaded()
+}
+
+/// A run condition that is true once the `group` has been requested and all of its resources
+/// have been inserted.
+pub fn resource_group_loaded(group: ResourceGroup) -> impl Fn(Res<ResourceHandles>) -> bool {
+    move |resource_handles| resource_handles.is_group_loaded(group)
+}
+
+fn start_loading<T: Resource + Asset + Clone + FromWorld>(
+    world: &mut World,
+    group: ResourceGroup,
+) -> WaitingResource {
+    let value = T::from_world(world);
+    let mut dependencies = Vec::new();
+    value.visit_dependencies(&mut |id| dependencies.push(id));
+    let assets = world.resource::<AssetServer>();
+    let handle = assets.add(value);
+    WaitingResource {
+        handle: handle.untyped(),
+        group,
+        dependencies,
+        insert: |world, handle| {
+            let assets = world.resource::<Assets<T>>();
+            if let Some(value) = assets.get(handle.id().typed::<T>()) {
+                world.insert_resource(value.clone());
+            }
+        },
+    }
+}
+
+/// A function that starts loading a resource registered in a group that wasn't requested yet.
+type StartLoading = fn(&mut World, ResourceGroup) -> WaitingResource;
+
 /// A function that inserts a loaded resource.
 type InsertLoadedResource = fn(&mut World, &UntypedHandle);
 
-#[derive(Resource, Default)]
-struct ResourceHandles {
+struct WaitingResource {
+    handle: UntypedHandle,
+    group: ResourceGroup,
+    /// The assets this resource is waiting for.
+    dependencies: Vec<UntypedAssetId>,
+    insert: InsertLoadedResource,
+}
+
+/// Keeps track of the resources queued with [`LoadResource::load_resource`].
+#[derive(Resource)]
+pub struct ResourceHandles {
     // Use a queue for waiting assets so they can be cycled through and moved to
     // `finished` one at a time.
-    waiting: VecDeque<(UntypedHandle, InsertLoadedResource)>,
+    waiting: VecDeque<WaitingResource>,
     finished: Vec<UntypedHandle>,
+    /// Resources of groups that haven't been requested yet.
+    pending: HashMap<ResourceGroup, Vec<StartLoading>>,
+    requested: HashSet<ResourceGroup>,
+    /// Number of dependencies of all resources in `finished`.
+    finished_dependencies: usize,
+    progress: LoadingProgress,
+    failed: Vec<FailedAsset>,
+}
+
+impl Default for ResourceHandles {
+    fn default() -> Self {
+        Self {
+            waiting: default(),
+            finished: default(),
+            pending: default(),
+            requested: HashSet::from_iter([ResourceGroup::STARTUP]),
+            finished_dependencies: 0,
+            progress: default(),
+            failed: default(),
+        }
+    }
+}
+
+impl ResourceHandles {
+    /// Whether every requested resource has been inserted.
+    pub fn is_all_loaded(&self) -> bool {
+        self.waiting.is_empty()
+    }
+
+    /// Whether the `group` has been requested and all of its resources have been inserted.
+    pub fn is_group_loaded(&self, group: ResourceGroup) -> bool {
+        self.requested.contains(&group)
+            && !self.waiting.iter().any(|waiting| waiting.group == group)
+    }
+
+    /// How many of the assets needed by the requested resources have loaded so far.
+    pub fn progress(&self) -> LoadingProgress {
+        self.progress
+    }
+
+    /// Assets that failed to load. Resources depending on them will never be inserted.
+    pub fn failed(&self) -> &[FailedAsset] {
+        &self.failed
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LoadingProgress {
+    pub loaded: usize,
+    pub total: usize,
+}
+
+impl LoadingProgress {
+    /// The loaded share of all assets, between 0 and 1.
+    pub fn fraction(self) -> f32 {
+        if self.total == 0 {
+            1.0
+        } else {
+            self.loaded as f32 / self.total as f32
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FailedAsset {
+    pub path: String,
+    pub error: String,
 }
 
 fn load_resource_assets(world: &mut World) {
     world.resource_scope(|world, mut resource_handles: Mut<ResourceHandles>| {
         world.resource_scope(|world, assets: Mut<AssetServer>| {
             for _ in 0..resource_handles.waiting.len() {
-                let (handle, insert_fn) = resource_handles.waiting.pop_front().unwrap();
-                if assets.is_loaded_with_dependencies(&handle) {
-                    insert_fn(world, &handle);
-                    resource_handles.finished.push(handle);
+                let waiting = resource_handles.waiting.pop_front().unwrap();
+                if assets.is_loaded_with_dependencies(&waiting.handle) {
+                    (waiting.insert)(world, &waiting.handle);
+                    resource_handles.finished_dependencies += waiting.dependencies.len();
+                    resource_handles.finished.push(waiting.handle);
                 } else {
-                    resource_handles.waiting.push_back((handle, insert_fn));
+                    resource_handles.waiting.push_back(waiting);
                 }
             }
+
+            let waiting_dependencies = resource_handles
+                .waiting
+                .iter()
+                .flat_map(|waiting| &waiting.dependencies);
+            let mut progress = LoadingProgress {
+                loaded: resource_handles.finished_dependencies,
+                total: resource_handles.finished_dependencies,
+            };
+            let mut failed = Vec::new();
+            for &id in waiting_dependencies {
+                progress.total += 1;
+                if assets.is_loaded_with_dependencies(id) {
+                    progress.loaded += 1;
+                } else if let Some(error) = load_error(&assets, id) {
+                    let path = assets
+                        .get_path(id)
+                        .map_or_else(|| format!("{id:?}"), |path| path.to_string());
+                    failed.push(FailedAsset { path, error });
+                }
+            }
+            resource_handles.progress = progress;
+            resource_handles.failed = failed;
         });
     });
 }
+
+/// The error that stopped an asset or one of its own dependencies from loading, if any.
+fn load_error(assets: &AssetServer, id: UntypedAssetId) -> Option<String> {
+    if let LoadState::Failed(error) = assets.load_state(id) {
+        return Some(error.to_string());
+    }
+    if let RecursiveDependencyLoadState::Failed(error) = assets.recursive_dependency_load_state(id)
+    {
+        return Some(error.to_string());
+    }
+    None
+}
@@ -0,0 +1,149 @@
+//! Sound categories, fading and per-state music playlists.
+
+pub mod playlist;
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Fade>();
+    app.add_systems(Update, fade_audio);
+
+    app.add_plugins(playlist::plugin);
+}
+
+/// An organizational marker component that should be added to a spawned [`AudioBundle`] if it is in the
+/// general "music" category (ex: global background music, soundtrack, etc).
+///
+/// This can then be used to query for and operate on sounds in that category. For example:
+///
+/// ```
+/// use bevy::prelude::*;
+/// use eat_o_perish::audio::Music;
+///
+/// fn set_music_volume(sink_query: Query<&AudioSink, With<Music>>) {
+///     for sink in &sink_query {
+///         sink.set_volume(0.5);
+///     }
+/// }
+/// ```
+#[derive(Component, Default)]
+pub struct Music;
+
+/// An organizational marker component that should be added to a spawned [`AudioBundle`] if it is in the
+/// general "sound effect" category (ex: footsteps, the sound of a magic spell, a door opening).
+///
+/// This can then be used to query for and operate on sounds in that category. For example:
+///
+/// ```
+/// use bevy::prelude::*;
+/// use eat_o_perish::audio::SoundEffect;
+///
+/// fn set_sound_effect_volume(sink_query: Query<&AudioSink, With<SoundEffect>>) {
+///     for sink in &sink_query {
+///         sink.set_volume(0.5);
+///     }
+/// }
+/// ```
+#[derive(Component, Default)]
+pub struct SoundEffect;
+
+/// Fades a sound in or out instead of starting or stopping it abruptly.
+///
+/// The volume set by the user's settings is multiplied by [`Fade::volume`], so a faded sound still
+/// follows the music and sound effect sliders. For example:
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use bevy::prelude::*;
+/// use eat_o_perish::audio::{Fade, Music};
+///
+/// fn stop_music(mut music_query: Query<&mut Fade, With<Music>>) {
+///     for mut fade in &mut music_query {
+///         fade.out_and_despawn(Duration::from_secs(2));
+///     }
+/// }
+/// ```
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct Fade {
+    /// The current volume factor.
+    pub volume: f32,
+    /// The volume factor being faded towards.
+    pub target: f32,
+    /// How much the volume factor changes per second.
+    pub speed: f32,
+    /// Whether to despawn the sound once it has faded to silence.
+    pub despawn_when_silent: bool,
+}
+
+impl Default for Fade {
+    fn default() -> Self {
+        Self {
+            volume: 1.0,
+            target: 1.0,
+            speed: 0.0,
+            despawn_when_silent: false,
+        }
+    }
+}
+
+impl Fade {
+    /// Starts silent and fades in to full volume over `duration`.
+    pub fn fade_in(duration: Duration) -> Self {
+        let mut fade = Self::silent();
+        fade.to(1.0, duration);
+        fade
+    }
+
+    /// Starts silent and stays silent until faded in with [`Fade::to`].
+    pub fn silent() -> Self {
+        Self {
+            volume: 0.0,
+            target: 0.0,
+            ..default()
+        }
+    }
+
+    /// Fades from the current volume factor to `target` over `duration`.
+    pub fn to(&mut self, target: f32, duration: Duration) {
+        self.target = target;
+        let secs = duration.as_secs_f32();
+        self.speed = if secs > 0.0 {
+            (target - self.volume).abs() / secs
+        } else {
+            f32::INFINITY
+        };
+    }
+
+    /// Fades out over `duration` and then despawns the sound.
+    pub fn out_and_despawn(&mut self, duration: Duration) {
+        self.to(0.0, duration);
+        self.despawn_when_silent = true;
+    }
+}
+
+/// Moves every [`Fade`] towards its target. This uses real time so that fades finish while the
+/// game is paused.
+fn fade_audio(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    mut fades: Query<(Entity, &mut Fade)>,
+) {
+    for (entity, mut fade) in &mut fades {
+        if fade.volume != fade.target {
+            let step = fade.speed * time.delta_secs();
+            let volume = if fade.volume < fade.target {
+                (fade.volume + step).min(fade.target)
+            } else {
+                (fade.volume - step).max(fade.target)
+            };
+            fade.volume = volume;
+        }
+        if fade.despawn_when_silent && fade.volume <= 0.0 {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
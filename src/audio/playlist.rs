@@ -0,0 +1,211 @@
+//! Background music that is declared per state and crossfades when the state changes.
+//!
+//! A [`Playlist`] is attached to a state with [`AddMusic::add_music`]. Entering the state starts
+//! its playlist, and leaving it fades the music out again. Moving between two states with the same
+//! playlist keeps the current track playing. The loading screen waits for the tracks of all
+//! playlists.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+
+use crate::{
+    asset_tracking::LoadResource,
+    audio::{Fade, Music},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<PlaylistTrack>();
+    app.init_resource::<MusicPlayer>();
+    app.add_systems(Update, play_music);
+}
+
+/// How long the old music fades out and the new music fades in when switching playlists.
+const CROSSFADE: Duration = Duration::from_millis(1500);
+
+/// A list of music tracks and how to play them. Playlists are the same if their tracks are.
+///
+/// ```
+/// use eat_o_perish::audio::playlist::Playlist;
+///
+/// let playlist = Playlist::new(["audio/music/Fluffing A Duck.ogg"]).looping();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Playlist {
+    tracks: Vec<&'static str>,
+    order: PlaylistOrder,
+    looping: bool,
+}
+
+/// The order in which the tracks of a [`Playlist`] are played.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PlaylistOrder {
+    /// As listed.
+    #[default]
+    InOrder,
+    /// In a random order, reshuffled every time the playlist starts over.
+    Shuffle,
+}
+
+impl Playlist {
+    /// Plays the tracks at the given asset paths once, in order.
+    pub fn new(tracks: impl IntoIterator<Item = &'static str>) -> Self {
+        Self {
+            tracks: tracks.into_iter().collect(),
+            order: default(),
+            looping: false,
+        }
+    }
+
+    pub fn shuffled(mut self) -> Self {
+        self.order = PlaylistOrder::Shuffle;
+        self
+    }
+
+    /// Starts over after the last track instead of falling silent.
+    pub fn looping(mut self) -> Self {
+        self.looping = true;
+        self
+    }
+}
+
+pub trait AddMusic {
+    /// Plays the `playlist` while in `state`. Its tracks start loading right away.
+    fn add_music<S: States>(&mut self, state: S, playlist: Playlist) -> &mut Self;
+}
+
+impl AddMusic for App {
+    fn add_music<S: States>(&mut self, state: S, playlist: Playlist) -> &mut Self {
+        let player = self.world().resource::<MusicPlayer>();
+        let existing = player
+            .playlists
+            .iter()
+            .position(|loaded| loaded.playlist == playlist);
+        let id = match existing {
+            Some(id) => id,
+            None => {
+                let assets = self.world().resource::<AssetServer>();
+                let tracks = playlist
+                    .tracks
+                    .iter()
+                    .map(|&path| assets.load(path))
+                    .collect::<Vec<_>>();
+                self.track_assets(tracks.iter().map(|track| track.clone().untyped()));
+                let mut player = self.world_mut().resource_mut::<MusicPlayer>();
+                player.playlists.push(LoadedPlaylist { playlist, tracks });
+                player.playlists.len() - 1
+            }
+        };
+
+        self.add_systems(OnEnter(state.clone()), request_playlist(Some(id)));
+        self.add_systems(OnExit(state), request_playlist(None))
+    }
+}
+
+struct LoadedPlaylist {
+    playlist: Playlist,
+    tracks: Vec<Handle<AudioSource>>,
+}
+
+impl LoadedPlaylist {
+    /// The track indices to play through once.
+    fn queue(&self) -> Vec<usize> {
+        let mut queue = (0..self.tracks.len()).collect::<Vec<_>>();
+        if self.playlist.order == PlaylistOrder::Shuffle {
+            queue.shuffle(&mut rand::thread_rng());
+        }
+        // Tracks are popped off the end.
+        queue.reverse();
+        queue
+    }
+}
+
+/// Keeps track of the playlists added with [`AddMusic::add_music`] and which one is playing.
+#[derive(Resource, Default)]
+struct MusicPlayer {
+    playlists: Vec<LoadedPlaylist>,
+    /// The playlist of the current state, if it has one.
+    requested: Option<usize>,
+    now_playing: Option<NowPlaying>,
+}
+
+struct NowPlaying {
+    playlist: usize,
+    /// Tracks that are still to come in this run through the playlist.
+    queue: Vec<usize>,
+    track: Option<Entity>,
+}
+
+/// Marks the music entity of a track from a [`Playlist`].
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct PlaylistTrack;
+
+fn request_playlist(playlist: Option<usize>) -> impl Fn(ResMut<MusicPlayer>) {
+    move |mut player| player.requested = playlist
+}
+
+/// Switches playlists once a state transition settles, and moves on to the next track when one
+/// ends.
+fn play_music(
+    mut commands: Commands,
+    mut player: ResMut<MusicPlayer>,
+    sinks: Query<&AudioSink, With<PlaylistTrack>>,
+    mut fades: Query<&mut Fade, With<PlaylistTrack>>,
+) {
+    let player = &mut *player;
+    let playing = player.now_playing.as_ref().map(|now| now.playlist);
+    if player.requested != playing {
+        let track = player.now_playing.take().and_then(|now| now.track);
+        if let Some(mut fade) = track.and_then(|track| fades.get_mut(track).ok()) {
+            fade.out_and_despawn(CROSSFADE);
+        }
+        player.now_playing = player.requested.map(|playlist| NowPlaying {
+            playlist,
+            queue: player.playlists[playlist].queue(),
+            track: None,
+        });
+    }
+
+    let Some(now) = &mut player.now_playing else {
+        return;
+    };
+    let finished = match now.track {
+        None => true,
+        // The track is still loading while it has no sink.
+        Some(track) => sinks.get(track).is_ok_and(AudioSink::empty),
+    };
+    if !finished {
+        return;
+    }
+    if let Some(track) = now.track.take() {
+        commands.entity(track).despawn_recursive();
+    }
+
+    let playlist = &player.playlists[now.playlist];
+    if now.queue.is_empty() && playlist.playlist.looping {
+        now.queue = playlist.queue();
+    }
+    let Some(index) = now.queue.pop() else {
+        return;
+    };
+    // A single looping track loops seamlessly instead of being respawned.
+    let playback = if playlist.playlist.looping && playlist.tracks.len() == 1 {
+        PlaybackSettings::LOOP
+    } else {
+        PlaybackSettings::ONCE
+    };
+    now.track = Some(
+        commands
+            .spawn((
+                Name::new("Playlist track"),
+                AudioPlayer(playlist.tracks[index].clone()),
+                playback,
+                Music,
+                PlaylistTrack,
+                Fade::fade_in(CROSSFADE),
+            ))
+            .id(),
+    );
+}
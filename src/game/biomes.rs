@@ -0,0 +1,238 @@
+//! Biomes: grassland, forest, desert and swamp regions that differ in how much food grows, which
+//! plants grow there, how fast critters walk and how far they see.
+//!
+//! Critters carry a heritable [`Habitat`] gene. In the biome they are adapted to they walk at full
+//! speed, so populations specialize in the regions they live in and can spread to others as
+//! mutations come along.
+//!
+//! Which biome a point belongs to is looked up in the [`BiomeMap`]. Generated terrain fills it in
+//! from its own noise, while the handcrafted world paints each [`FloorPlate`] with its [`Biome`]
+//! component once the plate has loaded. Plates without one are grassland.
+
+use bevy::{prelude::*, render::primitives::Aabb};
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::game::{
+    critters::FoodPellet,
+    level::{floor_plate_extents, FloorPlate},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Biome>();
+    app.register_type::<Habitat>();
+    app.register_type::<PlantKind>();
+    app.init_resource::<BiomeMap>();
+    app.add_systems(Update, (map_floor_plates, grow_plants));
+}
+
+/// Size of the handcrafted world's [`BiomeMap`].
+pub const WORLD_SIZE: f32 = 400.0;
+/// Width of a cell in the [`BiomeMap`].
+pub const BIOME_CELL_SIZE: f32 = 5.0;
+/// Chance that an offspring is adapted to a different biome than its parent.
+pub const HABITAT_MUTATION_CHANCE: f64 = 0.05;
+
+#[derive(
+    Component, Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, Reflect,
+)]
+#[reflect(Component)]
+pub enum Biome {
+    #[default]
+    Grassland,
+    Forest,
+    Desert,
+    Swamp,
+}
+
+impl Biome {
+    pub const ALL: [Self; 4] = [Self::Grassland, Self::Forest, Self::Desert, Self::Swamp];
+
+    /// How much food grows here compared to grassland.
+    pub fn food_density(self) -> f32 {
+        match self {
+            Self::Grassland => 1.0,
+            Self::Forest => 1.5,
+            Self::Desert => 0.2,
+            Self::Swamp => 0.8,
+        }
+    }
+
+    pub fn plant(self) -> PlantKind {
+        match self {
+            Self::Grassland => PlantKind::Grass,
+            Self::Forest => PlantKind::Berries,
+            Self::Desert => PlantKind::Cactus,
+            Self::Swamp => PlantKind::Reeds,
+        }
+    }
+
+    /// How fast critters that aren't adapted to this biome walk, compared to their full speed.
+    pub fn walk_speed(self) -> f32 {
+        match self {
+            Self::Grassland => 1.0,
+            Self::Forest => 0.75,
+            Self::Desert => 0.9,
+            Self::Swamp => 0.5,
+        }
+    }
+
+    /// How far critters see here, compared to grassland.
+    pub fn visibility(self) -> f32 {
+        match self {
+            Self::Grassland => 1.0,
+            Self::Forest => 0.5,
+            Self::Desert => 1.3,
+            Self::Swamp => 0.7,
+        }
+    }
+}
+
+/// The kind of plant a food pellet is, which decides how much energy eating it gives.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+pub enum PlantKind {
+    Grass,
+    Berries,
+    Cactus,
+    Reeds,
+}
+
+impl PlantKind {
+    pub fn nutrition(self) -> u32 {
+        match self {
+            Self::Grass | Self::Reeds => 1,
+            Self::Berries => 2,
+            // Cacti are rare, but worth the trip.
+            Self::Cactus => 3,
+        }
+    }
+}
+
+/// The biome a critter is adapted to. It walks at full speed there, whatever the terrain.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+pub struct Habitat(pub Biome);
+
+impl Habitat {
+    /// How fast the critter walks in `biome`, compared to its full speed.
+    pub fn walk_speed(self, biome: Biome) -> f32 {
+        if self.0 == biome {
+            1.0
+        } else {
+            biome.walk_speed()
+        }
+    }
+
+    /// The habitat of an offspring, which is usually the same as its parent's.
+    pub fn inherit(self, rng: &mut impl Rng) -> Self {
+        if rng.gen_bool(HABITAT_MUTATION_CHANCE) {
+            Self(*Biome::ALL.choose(rng).unwrap())
+        } else {
+            self
+        }
+    }
+}
+
+/// The biome of every point in the level, on a square grid centered on the origin. Points outside
+/// the grid are grassland.
+#[derive(Resource, Debug)]
+pub struct BiomeMap {
+    size: f32,
+    cells_per_side: usize,
+    cells: Vec<Biome>,
+}
+
+impl Default for BiomeMap {
+    fn default() -> Self {
+        Self::new(WORLD_SIZE)
+    }
+}
+
+impl BiomeMap {
+    /// A `size` by `size` map that is all grassland.
+    pub fn new(size: f32) -> Self {
+        let cells_per_side = (size / BIOME_CELL_SIZE).ceil() as usize;
+        Self {
+            size,
+            cells_per_side,
+            cells: vec![Biome::Grassland; cells_per_side * cells_per_side],
+        }
+    }
+
+    /// A `size` by `size` map with the biome of each cell given by `biome_at` at its center.
+    pub fn from_fn(size: f32, mut biome_at: impl FnMut(Vec2) -> Biome) -> Self {
+        let mut map = Self::new(size);
+        for z in 0..map.cells_per_side {
+            for x in 0..map.cells_per_side {
+                map.cells[z * map.cells_per_side + x] = biome_at(map.cell_center(x, z));
+            }
+        }
+        map
+    }
+
+    /// The biome at a point on the XZ plane.
+    pub fn biome_at(&self, point: Vec2) -> Biome {
+        self.cell(point).map_or(Biome::Grassland, |(x, z)| {
+            self.cells[z * self.cells_per_side + x]
+        })
+    }
+
+    /// Sets the biome of all cells whose center lies within `rect` on the XZ plane.
+    pub fn paint(&mut self, rect: Rect, biome: Biome) {
+        for z in 0..self.cells_per_side {
+            for x in 0..self.cells_per_side {
+                if rect.contains(self.cell_center(x, z)) {
+                    self.cells[z * self.cells_per_side + x] = biome;
+                }
+            }
+        }
+    }
+
+    fn cell(&self, point: Vec2) -> Option<(usize, usize)> {
+        let cell = ((point + self.size / 2.0) / BIOME_CELL_SIZE).floor();
+        let in_bounds = |value: f32| value >= 0.0 && (value as usize) < self.cells_per_side;
+        (in_bounds(cell.x) && in_bounds(cell.y)).then_some((cell.x as usize, cell.y as usize))
+    }
+
+    fn cell_center(&self, x: usize, z: usize) -> Vec2 {
+        (Vec2::new(x as f32, z as f32) + 0.5) * BIOME_CELL_SIZE - self.size / 2.0
+    }
+}
+
+/// Marks a floor plate whose biome is in the [`BiomeMap`].
+#[derive(Component)]
+struct Mapped;
+
+/// Paints the handcrafted world's floor plates into the [`BiomeMap`] as soon as their bounding
+/// boxes are known.
+fn map_floor_plates(
+    mut commands: Commands,
+    mut map: ResMut<BiomeMap>,
+    floor_plates: Query<(Entity, Option<&Biome>), (With<FloorPlate>, Without<Mapped>)>,
+    children: Query<&Children>,
+    bounds: Query<(&GlobalTransform, &Aabb)>,
+) {
+    for (entity, biome) in &floor_plates {
+        let Some((center, half_size)) = floor_plate_extents(entity, &children, &bounds) else {
+            continue;
+        };
+        map.paint(
+            Rect::from_center_half_size(center.xz(), half_size),
+            biome.copied().unwrap_or_default(),
+        );
+        commands.entity(entity).insert(Mapped);
+    }
+}
+
+/// Gives new food pellets the plant of the biome they landed in.
+fn grow_plants(
+    mut commands: Commands,
+    map: Res<BiomeMap>,
+    pellets: Query<(Entity, &Transform), (Added<FoodPellet>, Without<PlantKind>)>,
+) {
+    for (entity, transform) in &pellets {
+        let biome = map.biome_at(transform.translation.xz());
+        commands.entity(entity).insert(biome.plant());
+    }
+}
@@ -0,0 +1,226 @@
+//! The edges of the level. Critters and food pellets that leave it would otherwise fall forever,
+//! so each level decides what happens at its edges with a [`BoundaryMode`], and anything that
+//! drops below the kill plane is dealt with no matter the mode: critters die and food pellets are
+//! dropped back onto a food zone, or onto dry land if the level has none.
+//!
+//! The level's root is assumed to sit at the origin, so that the translations of the critters and
+//! food pellets in it are in world space.
+
+use std::time::Duration;
+
+use avian3d::prelude::*;
+use bevy::{prelude::*, time::common_conditions::on_timer};
+use rand::prelude::*;
+
+use crate::{
+    game::{
+        critters::{CritterDied, DeathCause, FoodPellet, Herbivore, Preditor},
+        food::FoodZoneCache,
+        terrain::Heightmap,
+    },
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<WorldBounds>();
+    app.add_systems(
+        Update,
+        enforce_bounds
+            .run_if(resource_exists::<WorldBounds>)
+            .run_if(on_timer(Duration::from_millis(250)))
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// How high the invisible walls of [`BoundaryMode::Walls`] reach.
+const WALL_HEIGHT: f32 = 200.0;
+const WALL_THICKNESS: f32 = 2.0;
+/// Food pellets that are dropped back onto the level fall from this high above the ground.
+const RECYCLE_HEIGHT: f32 = 20.0;
+/// Things that wrap around are lifted at least this high above the terrain on the other side, so
+/// that they don't end up inside a hill.
+const WRAP_CLEARANCE: f32 = 2.0;
+
+/// What happens to critters and food pellets that reach the edge of the level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+pub enum BoundaryMode {
+    /// Nothing stops them, so they fall off the edge and hit the kill plane.
+    KillPlane,
+    /// They come back in on the opposite side.
+    WrapAround,
+    /// Invisible walls keep them in.
+    Walls,
+}
+
+/// The square the current level fits in, centered on the origin, and what happens at its edges.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Resource)]
+pub struct WorldBounds {
+    pub half_size: f32,
+    /// Anything below this height has fallen out of the level.
+    pub kill_height: f32,
+    pub mode: BoundaryMode,
+}
+
+impl WorldBounds {
+    /// Whether a point is within the bounds on the XZ plane, at any height.
+    pub fn contains(&self, point: Vec3) -> bool {
+        point.x.abs() <= self.half_size && point.z.abs() <= self.half_size
+    }
+
+    /// A random point inside the bounds at the given height.
+    pub fn random_point(&self, height: f32, rng: &mut impl Rng) -> Vec3 {
+        Vec3::new(
+            rng.gen_range(-self.half_size..self.half_size),
+            height,
+            rng.gen_range(-self.half_size..self.half_size),
+        )
+    }
+}
+
+/// Inserts the level's [`WorldBounds`] and, if it has them, spawns its walls.
+pub fn spawn_bounds(world: &mut World, bounds: WorldBounds) {
+    world.insert_resource(bounds);
+    if bounds.mode != BoundaryMode::Walls {
+        return;
+    }
+    let length = bounds.half_size * 2.0 + WALL_THICKNESS * 2.0;
+    let offset = bounds.half_size + WALL_THICKNESS / 2.0;
+    for (translation, collider) in [
+        (
+            Vec3::new(0.0, 0.0, -offset),
+            Collider::cuboid(length, WALL_HEIGHT, WALL_THICKNESS),
+        ),
+        (
+            Vec3::new(0.0, 0.0, offset),
+            Collider::cuboid(length, WALL_HEIGHT, WALL_THICKNESS),
+        ),
+        (
+            Vec3::new(-offset, 0.0, 0.0),
+            Collider::cuboid(WALL_THICKNESS, WALL_HEIGHT, length),
+        ),
+        (
+            Vec3::new(offset, 0.0, 0.0),
+            Collider::cuboid(WALL_THICKNESS, WALL_HEIGHT, length),
+        ),
+    ] {
+        world.spawn((
+            Name::new("Invisible wall"),
+            Transform::from_translation(translation),
+            RigidBody::Static,
+            collider,
+            StateScoped(Screen::Gameplay),
+        ));
+    }
+}
+
+fn enforce_bounds(
+    mut commands: Commands,
+    bounds: Res<WorldBounds>,
+    mut entities: Query<
+        (
+            Entity,
+            &mut Transform,
+            &GlobalTransform,
+            Option<&mut LinearVelocity>,
+            Has<FoodPellet>,
+        ),
+        Or<(With<Herbivore>, With<Preditor>, With<FoodPellet>)>,
+    >,
+    food_zones: Res<FoodZoneCache>,
+    heightmap: Option<Res<Heightmap>>,
+    mut died: EventWriter<CritterDied>,
+) {
+    let mut rng = thread_rng();
+    let heightmap = heightmap.as_deref();
+    for (entity, mut transform, global_transform, velocity, is_food_pellet) in &mut entities {
+        let translation = global_transform.translation();
+        if translation.y >= bounds.kill_height {
+            if bounds.mode == BoundaryMode::WrapAround && !bounds.contains(translation) {
+                let size = bounds.half_size * 2.0;
+                let wrap =
+                    |value: f32| (value + bounds.half_size).rem_euclid(size) - bounds.half_size;
+                transform.translation.x = wrap(translation.x);
+                transform.translation.z = wrap(translation.z);
+                if let Some(heightmap) = heightmap {
+                    let ground = heightmap.height_at(transform.translation.xz());
+                    transform.translation.y = translation.y.max(ground + WRAP_CLEARANCE);
+                }
+            }
+            continue;
+        }
+        if is_food_pellet {
+            transform.translation = recycle_point(&food_zones, heightmap, &bounds, &mut rng);
+            if let Some(mut velocity) = velocity {
+                velocity.0 = Vec3::ZERO;
+            }
+        } else {
+            commands.entity(entity).despawn_recursive();
+            died.send(CritterDied {
+                critter: entity,
+                translation,
+                cause: DeathCause::FellOutOfWorld,
+            });
+        }
+    }
+}
+
+/// Where a food pellet that fell out of the level is dropped back in: above a food zone, or above
+/// dry land on terrain without any.
+fn recycle_point(
+    food_zones: &FoodZoneCache,
+    heightmap: Option<&Heightmap>,
+    bounds: &WorldBounds,
+    rng: &mut impl Rng,
+) -> Vec3 {
+    let ground = match (food_zones.random_point(rng), heightmap) {
+        // Zones on terrain follow its slopes, which can rise well above the zone's center.
+        (Some(point), Some(heightmap)) => point.with_y(heightmap.height_at(point.xz())),
+        (Some(point), None) => point,
+        (None, Some(heightmap)) => heightmap.random_land_point(rng),
+        (None, None) => bounds.random_point(0.0, rng),
+    };
+    ground + Vec3::Y * RECYCLE_HEIGHT
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+
+    use super::*;
+
+    const BOUNDS: WorldBounds = WorldBounds {
+        half_size: 120.0,
+        kill_height: -50.0,
+        mode: BoundaryMode::KillPlane,
+    };
+
+    #[test]
+    fn recycled_pellets_land_above_terrain() {
+        let heightmap = Heightmap::generate(7, BOUNDS.half_size * 2.0);
+        let mut rng = StdRng::seed_from_u64(7);
+        let zone_center = heightmap.random_land_point(&mut rng);
+        let with_zone = FoodZoneCache::from_zones([(zone_center, Vec2::splat(10.0), 1.0)]);
+        for food_zones in [FoodZoneCache::default(), with_zone] {
+            for _ in 0..100 {
+                let point = recycle_point(&food_zones, Some(&heightmap), &BOUNDS, &mut rng);
+                let ground = heightmap.height_at(point.xz());
+                assert!(point.y > ground, "{point} is under the ground at {ground}");
+                assert!(BOUNDS.contains(point));
+            }
+        }
+    }
+
+    #[test]
+    fn recycled_pellets_land_above_a_food_zone() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let zone_center = Vec3::new(30.0, 5.0, -20.0);
+        let half_size = Vec2::new(10.0, 4.0);
+        let food_zones = FoodZoneCache::from_zones([(zone_center, half_size, 1.0)]);
+        for _ in 0..100 {
+            let point = recycle_point(&food_zones, None, &BOUNDS, &mut rng);
+            assert!((point.xz() - zone_center.xz()).abs().cmple(half_size).all());
+            assert_eq!(point.y, zone_center.y + RECYCLE_HEIGHT);
+        }
+    }
+}
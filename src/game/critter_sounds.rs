@@ -0,0 +1,250 @@
+//! Spatial sound effects for critters: footsteps while walking, jumping and landing, eating and
+//! dying.
+//!
+//! Critters only report what happened by sending [`PlayCritterSound`]. The sounds closest to the
+//! camera are then played, up to [`MAX_CRITTER_VOICES`] at a time, so that a crowded world doesn't
+//! overwhelm the mixer.
+
+use avian3d::prelude::*;
+use bevy::{
+    audio::{SpatialScale, Volume},
+    prelude::*,
+};
+use bevy_tnua::{prelude::*, TnuaAction};
+use rand::prelude::*;
+
+use crate::{
+    asset_tracking::LoadResource,
+    audio::SoundEffect,
+    game::critters::{CritterAte, CritterDied},
+    AppSet,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<CritterAudio>();
+    app.register_type::<CritterSound>();
+    app.add_event::<PlayCritterSound>();
+    app.load_resource::<CritterSoundAssets>();
+    app.add_systems(
+        Update,
+        (
+            add_critter_audio,
+            (
+                walk_sounds,
+                eat_sounds,
+                death_sounds,
+                play_critter_sounds.run_if(resource_exists::<CritterSoundAssets>),
+            )
+                .chain(),
+        )
+            .in_set(AppSet::Update),
+    );
+}
+
+/// The most critter sounds that can play at the same time.
+pub const MAX_CRITTER_VOICES: usize = 24;
+/// Critters further away from the camera than this can't be heard.
+const MAX_AUDIBLE_DISTANCE: f32 = 500.0;
+/// Shrinks world distances before attenuating, since the camera usually floats far above the
+/// critters.
+const SPATIAL_SCALE: f32 = 0.02;
+/// How far a critter walks between two footsteps.
+const STRIDE_LENGTH: f32 = 2.0;
+/// Critters slower than this are standing still.
+const MIN_WALKING_SPEED: f32 = 0.5;
+
+#[derive(Resource, Asset, Reflect, Clone)]
+pub struct CritterSoundAssets {
+    #[dependency]
+    steps: Vec<Handle<AudioSource>>,
+}
+
+impl CritterSoundAssets {
+    pub const PATH_STEP_1: &'static str = "audio/sound_effects/step1.ogg";
+    pub const PATH_STEP_2: &'static str = "audio/sound_effects/step2.ogg";
+    pub const PATH_STEP_3: &'static str = "audio/sound_effects/step3.ogg";
+    pub const PATH_STEP_4: &'static str = "audio/sound_effects/step4.ogg";
+}
+
+impl FromWorld for CritterSoundAssets {
+    fn from_world(world: &mut World) -> Self {
+        let assets = world.resource::<AssetServer>();
+        Self {
+            steps: vec![
+                assets.load(Self::PATH_STEP_1),
+                assets.load(Self::PATH_STEP_2),
+                assets.load(Self::PATH_STEP_3),
+                assets.load(Self::PATH_STEP_4),
+            ],
+        }
+    }
+}
+
+/// Event requesting a critter sound at a position in the world.
+#[derive(Event, Debug)]
+pub struct PlayCritterSound {
+    pub kind: CritterSoundKind,
+    pub translation: Vec3,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CritterSoundKind {
+    Footstep,
+    Jump,
+    Land,
+    Eat,
+    Death,
+}
+
+impl CritterSoundKind {
+    /// Which step sample to play and how fast. There are no dedicated samples for anything but
+    /// footsteps yet, so the other sounds are pitched variations of them.
+    fn sample_and_speed(self, rng: &mut impl Rng) -> (usize, f32) {
+        match self {
+            Self::Footstep => (rng.gen_range(0..4), rng.gen_range(0.9..1.1)),
+            Self::Jump => (0, 1.4),
+            Self::Land => (3, 0.8),
+            Self::Eat => (1, 1.8),
+            Self::Death => (2, 0.5),
+        }
+    }
+
+    fn volume(self) -> f32 {
+        match self {
+            Self::Footstep => 0.5,
+            Self::Jump | Self::Land | Self::Eat => 0.8,
+            Self::Death => 1.0,
+        }
+    }
+}
+
+/// Tracks a critter's gait to know when to play footsteps and jump sounds.
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component)]
+struct CritterAudio {
+    /// Distance walked since the last footstep.
+    stride: f32,
+    jumping: bool,
+    airborne: bool,
+}
+
+/// Marks a playing critter sound, to count the voices in use.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct CritterSound;
+
+fn add_critter_audio(mut commands: Commands, critters: Query<Entity, Added<TnuaController>>) {
+    for entity in &critters {
+        commands.entity(entity).insert(CritterAudio::default());
+    }
+}
+
+fn walk_sounds(
+    time: Res<Time>,
+    mut critters: Query<(
+        &GlobalTransform,
+        &LinearVelocity,
+        &TnuaController,
+        &mut CritterAudio,
+    )>,
+    mut sounds: EventWriter<PlayCritterSound>,
+) {
+    for (transform, velocity, controller, mut audio) in &mut critters {
+        let translation = transform.translation();
+        let jumping = controller.action_name() == Some(TnuaBuiltinJump::NAME);
+        if jumping && !audio.jumping {
+            sounds.send(PlayCritterSound {
+                kind: CritterSoundKind::Jump,
+                translation,
+            });
+        }
+        audio.jumping = jumping;
+        // The jump action can end long before the critter is back on the ground, since AI critters
+        // only feed it once.
+        let airborne = controller.is_airborne().unwrap_or(false);
+        if audio.airborne && !airborne {
+            sounds.send(PlayCritterSound {
+                kind: CritterSoundKind::Land,
+                translation,
+            });
+        }
+        audio.airborne = airborne;
+
+        let speed = velocity.0.with_y(0.0).length();
+        if airborne || speed < MIN_WALKING_SPEED {
+            continue;
+        }
+        audio.stride += speed * time.delta_secs();
+        if audio.stride >= STRIDE_LENGTH {
+            audio.stride = 0.0;
+            sounds.send(PlayCritterSound {
+                kind: CritterSoundKind::Footstep,
+                translation,
+            });
+        }
+    }
+}
+
+fn eat_sounds(
+    mut ate: EventReader<CritterAte>,
+    critters: Query<&GlobalTransform>,
+    mut sounds: EventWriter<PlayCritterSound>,
+) {
+    for event in ate.read() {
+        if let Ok(transform) = critters.get(event.eater) {
+            sounds.send(PlayCritterSound {
+                kind: CritterSoundKind::Eat,
+                translation: transform.translation(),
+            });
+        }
+    }
+}
+
+fn death_sounds(mut died: EventReader<CritterDied>, mut sounds: EventWriter<PlayCritterSound>) {
+    for event in died.read() {
+        sounds.send(PlayCritterSound {
+            kind: CritterSoundKind::Death,
+            translation: event.translation,
+        });
+    }
+}
+
+/// Plays the requested sounds closest to the camera, as long as there are voices left.
+fn play_critter_sounds(
+    mut commands: Commands,
+    mut requests: EventReader<PlayCritterSound>,
+    listener: Query<&GlobalTransform, With<SpatialListener>>,
+    playing: Query<(), With<CritterSound>>,
+    assets: Res<CritterSoundAssets>,
+) {
+    let Ok(listener) = listener.get_single() else {
+        requests.clear();
+        return;
+    };
+    let listener = listener.translation();
+    let free_voices = MAX_CRITTER_VOICES.saturating_sub(playing.iter().count());
+
+    let mut audible = requests
+        .read()
+        .map(|request| (request, request.translation.distance(listener)))
+        .filter(|(_, distance)| *distance <= MAX_AUDIBLE_DISTANCE)
+        .collect::<Vec<_>>();
+    audible.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+
+    let mut rng = thread_rng();
+    for (request, _) in audible.into_iter().take(free_voices) {
+        let (sample, speed) = request.kind.sample_and_speed(&mut rng);
+        commands.spawn((
+            Name::new("Critter Sound"),
+            AudioPlayer(assets.steps[sample].clone()),
+            PlaybackSettings::DESPAWN
+                .with_spatial(true)
+                .with_spatial_scale(SpatialScale::new(SPATIAL_SCALE))
+                .with_speed(speed)
+                .with_volume(Volume::new(request.kind.volume())),
+            Transform::from_translation(request.translation),
+            SoundEffect,
+            CritterSound,
+        ));
+    }
+}
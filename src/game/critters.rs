@@ -1,28 +1,33 @@
-use std::{
-    time::Duration,
-};
+use std::time::Duration;
 
 use rand::prelude::*;
 
-
+use avian3d::prelude::*;
 use bevy::{
     prelude::*,
     time::common_conditions::on_timer,
+    utils::{HashMap, HashSet},
 };
+use bevy_spatial::{kdtree::KDTree3, SpatialAccess};
 use bevy_tnua::prelude::*;
-use avian3d::prelude::*;
-use bevy_spatial::{SpatialAccess, kdtree::KDTree3};
-use blenvy::{
-    AddToGameWorld, BlueprintInfo,
-    HideUntilReady, SpawnBlueprint,
-};
-
+use blenvy::{AddToGameWorld, BlueprintInfo, HideUntilReady, SpawnBlueprint};
 
-use rand::random;
+use super::{
+    biomes::{BiomeMap, Habitat, PlantKind},
+    crowd::{CritterDetail, CrowdAssets, KinematicMovement, Lightweight},
+    day_cycle::DayCycle,
+    level::LevelAssets,
+    navigation::{Navigation, PathRequests},
+    seasons::Climate,
+    spatial::SpatialGrid,
+    water::{DroughtTolerance, Hydration, WaterSource},
+    Player,
+};
+use crate::screens::Screen;
 
 #[derive(Component, Reflect)]
 #[reflect(Component)]
-pub struct Energy(u32);
+pub struct Energy(pub u32);
 
 #[derive(Component, Reflect)]
 #[reflect(Component)]
@@ -38,16 +43,41 @@ pub struct FoodPellet;
 
 #[derive(Component, Reflect)]
 #[reflect(Component)]
-pub struct Speed(f32);
+pub struct Speed(pub f32);
 
 #[derive(Component, Reflect)]
 #[reflect(Component)]
-pub struct ReproductionEnergy(f32);
+pub struct ReproductionEnergy(pub f32);
+
+/// Heritable preference for being active at night, between 0 (diurnal) and 1 (nocturnal).
+/// Critters rest and move slowly at the time of day that suits them least.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct Nocturnality(pub f32);
+
+/// How fast a critter moves at the time of day that suits it least, compared to its best.
+const RESTING_SPEED: f32 = 0.3;
+
+impl Nocturnality {
+    /// How active the critter is with the given daylight, between 0 and 1.
+    pub fn activity(&self, daylight: f32) -> f32 {
+        1.0 - (daylight - (1.0 - self.0)).abs()
+    }
+
+    /// How fast the critter moves with the given daylight, compared to its best.
+    pub fn pace(&self, daylight: f32) -> f32 {
+        RESTING_SPEED.lerp(1.0, self.activity(daylight))
+    }
+}
 
 #[derive(Component, Reflect)]
 #[reflect(Component)]
 pub struct Critter;
 
+/// How likely a critter is to jump each time it steers.
+pub const JUMP_CHANCE: f64 = 0.1;
+pub const JUMP_HEIGHT: f32 = 4.0;
+
 #[derive(Default, Component, Reflect)]
 #[reflect(Component)]
 pub struct Preditor;
@@ -56,136 +86,332 @@ pub struct Preditor;
 #[reflect(Component)]
 pub struct Herbivore;
 
+/// What sets the kinds of critters apart from each other.
+trait Species: Component {
+    /// What critters of this kind go after, found through its [`SpatialGrid`].
+    type Food: Component;
+    /// What lets critters of this kind eat their food.
+    type Eater: Component;
+    const EATER: Self::Eater;
+    /// The blueprint of critters with full physics.
+    const BLUEPRINT: &'static str;
+
+    /// The body of critters in performance mode, see [`CritterDetail`].
+    fn crowd_body(assets: &CrowdAssets) -> impl Bundle;
+}
+
+impl Species for Herbivore {
+    type Food = FoodPellet;
+    type Eater = PelletEater;
+    const EATER: PelletEater = PelletEater;
+    const BLUEPRINT: &'static str = LevelAssets::PATH_HERBIVORE;
+
+    fn crowd_body(assets: &CrowdAssets) -> impl Bundle {
+        assets.herbivore()
+    }
+}
+
+impl Species for Preditor {
+    type Food = Herbivore;
+    type Eater = CritterEater;
+    const EATER: CritterEater = CritterEater;
+    const BLUEPRINT: &'static str = LevelAssets::PATH_PREDITOR;
+
+    fn crowd_body(assets: &CrowdAssets) -> impl Bundle {
+        assets.preditor()
+    }
+}
+
+/// Marks a food pellet or critter that was eaten this frame, so that nothing else eats it as well.
+/// Everything eaten is despawned together at the end of the frame.
+#[derive(Component, Debug)]
+pub struct Eaten;
+
+/// Full critters eating what they touch. Anything else that eats runs after it, so that it leaves
+/// what was [`Eaten`] alone.
+#[derive(SystemSet, Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct EatingSet;
+
+/// Sent when a critter eats a food pellet or another critter.
+#[derive(Event, Debug)]
+pub struct CritterAte {
+    pub eater: Entity,
+}
+
+/// Sent when a critter gives birth.
+#[derive(Event, Debug)]
+pub struct CritterBorn {
+    pub parent: Entity,
+    pub child: Entity,
+}
+
+/// Sent when a critter dies.
+#[derive(Event, Debug)]
+pub struct CritterDied {
+    pub critter: Entity,
+    /// Where the critter died, since it is despawned right away.
+    pub translation: Vec3,
+    pub cause: DeathCause,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub enum DeathCause {
+    Starvation,
+    Thirst,
+    Eaten,
+    FellOutOfWorld,
+    /// Erased with the sandbox tools.
+    Removed,
+}
+
+impl DeathCause {
+    pub const ALL: [Self; 5] = [
+        Self::Starvation,
+        Self::Thirst,
+        Self::Eaten,
+        Self::FellOutOfWorld,
+        Self::Removed,
+    ];
+
+    /// What happened to critters that died of this, as in "3 starved".
+    pub fn verb(self) -> &'static str {
+        match self {
+            Self::Starvation => "starved",
+            Self::Thirst => "died of thirst",
+            Self::Eaten => "eaten",
+            Self::FellOutOfWorld => "fell out of the world",
+            Self::Removed => "removed",
+        }
+    }
+}
+
+/// How many critters died of each cause since the level started.
+#[derive(Resource, Debug, Default)]
+pub struct DeathTally(pub HashMap<DeathCause, u32>);
+
+impl DeathTally {
+    /// The counts of all causes that killed any critters, like "12 starved, 3 eaten".
+    pub fn summary(&self) -> String {
+        let counts = DeathCause::ALL
+            .into_iter()
+            .filter_map(|cause| {
+                self.0
+                    .get(&cause)
+                    .map(|count| format!("{count} {}", cause.verb()))
+            })
+            .collect::<Vec<_>>();
+        if counts.is_empty() {
+            "none".to_string()
+        } else {
+            counts.join(", ")
+        }
+    }
+}
 
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<FoodPellet>();
     app.register_type::<Preditor>();
     app.register_type::<Herbivore>();
     app.register_type::<Critter>();
-    app.add_systems(Update, (
-        spawn_herbivores,
-        spawn_preditors,
-        herbivore_movement.run_if(on_timer(Duration::from_millis(500))),
-        preditor_movement.run_if(on_timer(Duration::from_millis(500))),
-        consume_energy.run_if(on_timer(Duration::from_secs(2))),
-        reproduce::<Herbivore>.run_if(on_timer(Duration::from_secs(1))),
-        reproduce::<Preditor>.run_if(on_timer(Duration::from_secs(1))),
-        eat_pellet,
-        eat_critter,
-    ));
+    app.register_type::<Nocturnality>();
+    app.add_event::<CritterAte>();
+    app.add_event::<CritterBorn>();
+    app.add_event::<CritterDied>();
+    app.init_resource::<DeathTally>();
+    app.add_systems(OnEnter(Screen::Gameplay), reset_death_tally);
+    app.add_systems(
+        Update,
+        (
+            spawn_critters::<Herbivore>,
+            spawn_critters::<Preditor>,
+            critter_movement::<Herbivore>.run_if(on_timer(Duration::from_millis(500))),
+            critter_movement::<Preditor>.run_if(on_timer(Duration::from_millis(500))),
+            consume_energy.run_if(on_timer(Duration::from_secs(2))),
+            reproduce::<Herbivore>.run_if(on_timer(Duration::from_secs(1))),
+            reproduce::<Preditor>.run_if(on_timer(Duration::from_secs(1))),
+            (eat_pellet, eat_critter).chain().in_set(EatingSet),
+            tally_deaths,
+        ),
+    );
+    app.add_systems(PostUpdate, despawn_eaten);
 }
 
-fn spawn_herbivores(
-    mut commands: Commands,
-    query: Query<(Entity, Option<&Speed>, Option<&ReproductionEnergy>, Option<&Energy>), Added<Herbivore>>,
-) {
-    for (entity, maybe_speed, maybe_reproduction_energy, maybe_energy) in &query {
-        commands.entity(entity).insert((
-            BlueprintInfo::from_path("blueprints/Herbivore.glb"),
-            SpawnBlueprint,
-            HideUntilReady,
-            AddToGameWorld,
-            CollidingEntities::default(),
-            PelletEater,
-        ));
-        if maybe_energy.is_none() {
-            commands.entity(entity).insert(Energy(10));
-        }
-        if maybe_speed.is_none() {
-            commands.entity(entity).insert(Speed(thread_rng().gen_range(0.5..2.0)));
-        }
-        if maybe_reproduction_energy.is_none() {
-            commands.entity(entity).insert(ReproductionEnergy(thread_rng().gen_range(1.0..20.0)));
-        }
+fn reset_death_tally(mut tally: ResMut<DeathTally>) {
+    tally.0.clear();
+}
+
+fn tally_deaths(mut died: EventReader<CritterDied>, mut tally: ResMut<DeathTally>) {
+    for death in died.read() {
+        *tally.0.entry(death.cause).or_default() += 1;
     }
 }
 
-fn spawn_preditors(
+fn spawn_critters<T: Species>(
     mut commands: Commands,
-    query: Query<(Entity, Option<&Speed>, Option<&ReproductionEnergy>, Option<&Energy>), Added<Preditor>>,
+    query: Query<
+        (
+            Entity,
+            Option<&Speed>,
+            Option<&ReproductionEnergy>,
+            Option<&Energy>,
+            Option<&Nocturnality>,
+            Option<&Habitat>,
+            Option<&Hydration>,
+            Option<&DroughtTolerance>,
+            &Transform,
+        ),
+        Added<T>,
+    >,
+    biome_map: Res<BiomeMap>,
+    detail: Res<CritterDetail>,
+    crowd_assets: Res<CrowdAssets>,
 ) {
-    for (entity, maybe_speed, maybe_reproduction_energy, maybe_energy) in &query {
-        commands.entity(entity).insert((
-            BlueprintInfo::from_path("blueprints/Preditor.glb"),
-            SpawnBlueprint,
-            HideUntilReady,
-            AddToGameWorld,
-            CollidingEntities::default(),
-            CritterEater,
-        ));
+    for (
+        entity,
+        maybe_speed,
+        maybe_reproduction_energy,
+        maybe_energy,
+        maybe_nocturnality,
+        maybe_habitat,
+        maybe_hydration,
+        maybe_drought_tolerance,
+        transform,
+    ) in &query
+    {
+        if *detail == CritterDetail::Performance {
+            commands.entity(entity).insert((
+                T::crowd_body(&crowd_assets),
+                AddToGameWorld,
+                T::EATER,
+            ));
+        } else {
+            commands.entity(entity).insert((
+                BlueprintInfo::from_path(T::BLUEPRINT),
+                SpawnBlueprint,
+                HideUntilReady,
+                AddToGameWorld,
+                CollidingEntities::default(),
+                T::EATER,
+                Navigation::default(),
+            ));
+        }
         if maybe_energy.is_none() {
             commands.entity(entity).insert(Energy(10));
         }
         if maybe_speed.is_none() {
-            commands.entity(entity).insert(Speed(thread_rng().gen_range(0.5..2.0)));
+            commands
+                .entity(entity)
+                .insert(Speed(thread_rng().gen_range(0.5..2.0)));
         }
         if maybe_reproduction_energy.is_none() {
-            commands.entity(entity).insert(ReproductionEnergy(thread_rng().gen_range(1.0..20.0)));
+            commands
+                .entity(entity)
+                .insert(ReproductionEnergy(thread_rng().gen_range(1.0..20.0)));
         }
-    }
-}
-
-
-fn herbivore_movement(
-    mut query: Query<(&mut TnuaController, &GlobalTransform, &Speed), With<Herbivore>>,
-    treeaccess: Res<KDTree3<FoodPellet>>,
-) {
-    for (mut controller, transform, speed) in &mut query {
-        let mut rng = rand::thread_rng();
-        let (x,z) = if let Some((pos, _entity)) = treeaccess.nearest_neighbour(transform.translation()) {
-            let x = pos.x - transform.translation().x;
-            let z = pos.z - transform.translation().z;
-            let a = z.atan2(x);
-            (a.cos(),a.sin())
-        } else {
-            (rng.gen_range(-1.0..1.0),
-             rng.gen_range(-1.0..1.0))
-        };
-        let direction = Vec3::new(x, 0.0, z);
-        let jumping = random::<f32>() > 0.9;
-
-        controller.basis(TnuaBuiltinWalk {
-            desired_velocity: direction.normalize_or_zero() * 5.0 * speed.0,
-            float_height: 1.5,
-            ..Default::default()
-        });
-
-        if jumping {
-            controller.action(TnuaBuiltinJump {
-                height: 4.0,
-                ..Default::default()
-            });
+        if maybe_nocturnality.is_none() {
+            commands
+                .entity(entity)
+                .insert(Nocturnality(thread_rng().gen_range(0.0..1.0)));
+        }
+        // The first generation is adapted to wherever it happens to be placed.
+        if maybe_habitat.is_none() {
+            commands
+                .entity(entity)
+                .insert(Habitat(biome_map.biome_at(transform.translation.xz())));
+        }
+        if maybe_hydration.is_none() {
+            commands.entity(entity).insert(Hydration::default());
+        }
+        if maybe_drought_tolerance.is_none() {
+            commands
+                .entity(entity)
+                .insert(DroughtTolerance(thread_rng().gen_range(0.0..1.0)));
         }
     }
 }
 
-fn preditor_movement(
-    mut query: Query<(&mut TnuaController, &GlobalTransform, &Speed), With<Preditor>>,
-    treeaccess: Res<KDTree3<Herbivore>>,
+fn critter_movement<T: Species>(
+    mut query: Query<
+        (
+            Entity,
+            Option<&mut TnuaController>,
+            Option<&mut KinematicMovement>,
+            &mut Navigation,
+            &GlobalTransform,
+            &Speed,
+            &Nocturnality,
+            &Habitat,
+            &Hydration,
+        ),
+        (
+            With<T>,
+            Without<Player>,
+            Or<(With<TnuaController>, With<KinematicMovement>)>,
+        ),
+    >,
+    food_grid: Res<SpatialGrid<T::Food>>,
+    water_tree: Res<KDTree3<WaterSource>>,
+    cycle: Res<DayCycle>,
+    biome_map: Res<BiomeMap>,
+    mut path_requests: ResMut<PathRequests>,
 ) {
-    for (mut controller, transform, speed) in &mut query {
+    let daylight = cycle.daylight();
+    for (
+        entity,
+        controller,
+        movement,
+        mut navigation,
+        transform,
+        speed,
+        nocturnality,
+        habitat,
+        hydration,
+    ) in &mut query
+    {
         let mut rng = rand::thread_rng();
-        let (x,z) = if let Some((pos, _entity)) = treeaccess.nearest_neighbour(transform.translation()) {
-            let x = pos.x - transform.translation().x;
-            let z = pos.z - transform.translation().z;
-            let a = z.atan2(x);
-            (a.cos(),a.sin())
+        let biome = biome_map.biome_at(transform.translation().xz());
+        let pace = nocturnality.pace(daylight) * habitat.walk_speed(biome);
+        let sight_range = cycle.sight_range() * biome.visibility();
+        // Targets out of sight are not known about, so the critter wanders instead. Thirsty
+        // critters go for water before food.
+        let in_sight = |pos: Vec3| pos.distance(transform.translation()) <= sight_range;
+        let direction = if let Some(pos) = water_tree
+            .nearest_neighbour(transform.translation())
+            .map(|(pos, _)| pos)
+            .filter(|pos| hydration.is_thirsty() && in_sight(*pos))
+            .or_else(|| {
+                food_grid
+                    .nearest_within(transform.translation(), sight_range)
+                    .map(|(pos, _)| pos)
+            }) {
+            // Around obstacles rather than straight into them.
+            navigation.steer(entity, transform.translation(), pos, &mut path_requests)
         } else {
-            (rng.gen_range(-1.0..1.0),
-             rng.gen_range(-1.0..1.0))
+            navigation.stop();
+            Vec3::new(rng.gen_range(-1.0..1.0), 0.0, rng.gen_range(-1.0..1.0))
         };
-        let direction = Vec3::new(x, 0.0, z);
-        let jumping = random::<f32>() > 0.9;
+        let velocity = direction.normalize_or_zero() * 5.0 * speed.0 * pace;
+        let jumping = rng.gen_bool(JUMP_CHANCE);
 
+        // Critters far from the camera glide instead, see `lod`.
+        let Some(mut controller) = controller else {
+            if let Some(mut movement) = movement {
+                movement.velocity = velocity;
+                if jumping {
+                    movement.jump(JUMP_HEIGHT);
+                }
+            }
+            continue;
+        };
         controller.basis(TnuaBuiltinWalk {
-            desired_velocity: direction.normalize_or_zero() * 5.0 * speed.0,
+            desired_velocity: velocity,
             float_height: 1.5,
             ..Default::default()
         });
 
         if jumping {
             controller.action(TnuaBuiltinJump {
-                height: 4.0,
+                height: JUMP_HEIGHT,
                 ..Default::default()
             });
         }
@@ -194,16 +420,27 @@ fn preditor_movement(
 
 fn eat_pellet(
     mut commands: Commands,
-    mut query: Query<(&CollidingEntities, Option<&mut Energy>), With<PelletEater>>,
-    food_pellets: Query<Entity, With<FoodPellet>>,
+    mut query: Query<
+        (Entity, &CollidingEntities, Option<&mut Energy>),
+        (With<PelletEater>, Without<Lightweight>, Without<Eaten>),
+    >,
+    food_pellets: Query<Option<&PlantKind>, (With<FoodPellet>, Without<Eaten>)>,
+    mut ate: EventWriter<CritterAte>,
 ) {
-    for (colliding_entities, mut energy) in &mut query {
+    // The markers only show up in queries once this system is done.
+    let mut eaten = HashSet::new();
+    for (eater, colliding_entities, mut energy) in &mut query {
         for entity in &colliding_entities.0 {
-            if food_pellets.contains(*entity) {
-                commands.entity(*entity).despawn_recursive();
+            if eaten.contains(entity) {
+                continue;
+            }
+            if let Ok(plant) = food_pellets.get(*entity) {
+                eaten.insert(*entity);
+                commands.entity(*entity).insert(Eaten);
                 if let Some(energy) = energy.as_mut() {
-                    energy.0 += 1;
+                    energy.0 += plant.map_or(1, |plant| plant.nutrition());
                 }
+                ate.send(CritterAte { eater });
             }
         }
     }
@@ -211,51 +448,132 @@ fn eat_pellet(
 
 fn eat_critter(
     mut commands: Commands,
-    mut query: Query<(&CollidingEntities, Option<&mut Energy>), With<CritterEater>>,
-    critters: Query<Entity, With<Critter>>,
+    mut query: Query<
+        (Entity, &CollidingEntities, Option<&mut Energy>),
+        (With<CritterEater>, Without<Lightweight>, Without<Eaten>),
+    >,
+    critters: Query<&GlobalTransform, (With<Critter>, Without<Eaten>)>,
+    mut ate: EventWriter<CritterAte>,
+    mut died: EventWriter<CritterDied>,
 ) {
-    for (colliding_entities, mut energy) in &mut query {
+    // The markers only show up in queries once this system is done.
+    let mut eaten = HashSet::new();
+    for (eater, colliding_entities, mut energy) in &mut query {
+        if eaten.contains(&eater) {
+            continue;
+        }
         for entity in &colliding_entities.0 {
-            if critters.contains(*entity) {
-                commands.entity(*entity).despawn_recursive();
+            if eaten.contains(entity) {
+                continue;
+            }
+            if let Ok(transform) = critters.get(*entity) {
+                eaten.insert(*entity);
+                commands.entity(*entity).insert(Eaten);
                 if let Some(energy) = energy.as_mut() {
                     energy.0 += 10;
                 }
+                ate.send(CritterAte { eater });
+                died.send(CritterDied {
+                    critter: *entity,
+                    translation: transform.translation(),
+                    cause: DeathCause::Eaten,
+                });
             }
         }
     }
 }
 
+fn despawn_eaten(mut commands: Commands, query: Query<Entity, With<Eaten>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
 fn consume_energy(
     mut commands: Commands,
-    mut query: Query<(Entity, &mut Energy)>,
+    mut query: Query<(
+        Entity,
+        &mut Energy,
+        Option<&DroughtTolerance>,
+        &GlobalTransform,
+    )>,
+    mut died: EventWriter<CritterDied>,
+    climate: Res<Climate>,
 ) {
-    for (entity, mut energy) in &mut query {
+    let mut rng = thread_rng();
+    let metabolism_cost = climate.metabolism_cost();
+    for (entity, mut energy, drought_tolerance, transform) in &mut query {
         if energy.0 == 0 {
             commands.entity(entity).despawn_recursive();
+            died.send(CritterDied {
+                critter: entity,
+                translation: transform.translation(),
+                cause: DeathCause::Starvation,
+            });
         } else {
-            energy.0 -= 1;
+            let metabolism_cost =
+                metabolism_cost * drought_tolerance.map_or(1.0, DroughtTolerance::metabolism_cost);
+            // Fractional costs are paid on average, by sometimes rounding up.
+            let cost = metabolism_cost.trunc() as u32
+                + u32::from(rng.gen_bool(metabolism_cost.fract().into()));
+            energy.0 = energy.0.saturating_sub(cost);
         }
     }
 }
 
 fn reproduce<T: Default + Component>(
     mut commands: Commands,
-    mut query: Query<(&mut Energy, &ReproductionEnergy, &Speed, &GlobalTransform), With<T>>,
+    mut query: Query<
+        (
+            Entity,
+            &mut Energy,
+            &ReproductionEnergy,
+            &Speed,
+            &Nocturnality,
+            &Habitat,
+            &DroughtTolerance,
+            &GlobalTransform,
+        ),
+        With<T>,
+    >,
+    mut born: EventWriter<CritterBorn>,
+    climate: Res<Climate>,
 ) {
     let mut rng = thread_rng();
-    for (mut energy, reproduction_energy, speed, transform) in &mut query {
-        if energy.0 as f32 > reproduction_energy.0*1.5 {
+    for (
+        parent,
+        mut energy,
+        reproduction_energy,
+        speed,
+        nocturnality,
+        habitat,
+        drought_tolerance,
+        transform,
+    ) in &mut query
+    {
+        if energy.0 as f32 > reproduction_energy.0 * 1.5 {
             energy.0 -= reproduction_energy.0 as u32;
+            // Offspring that aren't viable in the current season cost energy all the same.
+            if !rng.gen_bool(climate.reproduction_viability().into()) {
+                continue;
+            }
             let new_speed = (speed.0 + rng.gen_range(-1.0..1.0)).max(0.0);
-            let new_reproduction_energy = (reproduction_energy.0 + rng.gen_range(-1.0..1.0)).max(0.0);
-            commands.spawn((
-                T::default(),
-                Energy(reproduction_energy.0 as u32),
-                Speed(new_speed),
-                ReproductionEnergy(new_reproduction_energy),
-                Transform::from(*transform),
-            ));
+            let new_reproduction_energy =
+                (reproduction_energy.0 + rng.gen_range(-1.0..1.0)).max(0.0);
+            let new_nocturnality = (nocturnality.0 + rng.gen_range(-0.1..0.1)).clamp(0.0, 1.0);
+            let child = commands
+                .spawn((
+                    T::default(),
+                    Energy(reproduction_energy.0 as u32),
+                    Speed(new_speed),
+                    ReproductionEnergy(new_reproduction_energy),
+                    Nocturnality(new_nocturnality),
+                    habitat.inherit(&mut rng),
+                    drought_tolerance.inherit(&mut rng),
+                    Transform::from(*transform),
+                ))
+                .id();
+            born.send(CritterBorn { parent, child });
         }
     }
 }
@@ -0,0 +1,407 @@
+//! Performance mode, for levels with thousands of critters.
+//!
+//! A full critter is a blenvy blueprint with a Tnua controller and a dynamic Avian body, which caps
+//! a level at a few hundred of them. In performance mode critters are [`Lightweight`] instead: they
+//! glide over the ground kinematically without colliders, all critters of a species share one mesh
+//! and material so that they are drawn as GPU instances, and instead of eating what they bump into
+//! they eat whatever a full critter in their place would be touching. Sensing is batched: every
+//! frame a different slice of them looks around, in parallel, so each critter still picks a new
+//! heading about as often as a full one.
+//!
+//! Critters in performance mode have no colliders, so they can't be picked for possession in the
+//! sandbox. The survivor of a survival run is still possessed, and glides wherever the player steers
+//! it.
+
+use std::time::Duration;
+
+use bevy::{
+    diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin},
+    prelude::*,
+    time::common_conditions::on_timer,
+    ui::Val::*,
+    utils::HashSet,
+};
+use bevy_spatial::{kdtree::KDTree3, SpatialAccess};
+use rand::prelude::*;
+
+use crate::{
+    game::{
+        biomes::{BiomeMap, Habitat, PlantKind},
+        critters::{
+            CritterAte, CritterDied, DeathCause, Eaten, EatingSet, Energy, FoodPellet, Herbivore,
+            Nocturnality, Preditor, Speed, JUMP_CHANCE, JUMP_HEIGHT,
+        },
+        day_cycle::DayCycle,
+        navigation::{NavGrid, Navigation},
+        spatial::SpatialGrid,
+        terrain::Heightmap,
+        water::{Hydration, WaterSource},
+        Player,
+    },
+    screens::Screen,
+    theme::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_plugins(FrameTimeDiagnosticsPlugin);
+    app.register_type::<CritterDetail>();
+    app.register_type::<Lightweight>();
+    app.register_type::<KinematicMovement>();
+    app.init_resource::<CritterDetail>();
+    app.init_resource::<CrowdAssets>();
+    app.add_systems(
+        Update,
+        (
+            (sense, glide).chain(),
+            eat_within_reach
+                .after(EatingSet)
+                .run_if(on_timer(Duration::from_millis(100))),
+        ),
+    );
+    app.add_systems(
+        Update,
+        (
+            spawn_performance_stats.run_if(resource_changed::<CritterDetail>),
+            update_performance_stats.run_if(on_timer(Duration::from_millis(500))),
+        )
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// Each lightweight critter looks around once every this many frames, which is about as often as
+/// full critters steer at 60 FPS.
+const SENSING_BATCHES: u32 = 30;
+/// Top speed per unit of the [`Speed`] gene, the same as for full critters.
+const WALK_SPEED: f32 = 5.0;
+/// How high above the ground lightweight critters glide, the same as the float height of full
+/// critters.
+const HOVER_HEIGHT: f32 = 1.5;
+/// Half the width of the cube that is a full critter's collider.
+const CRITTER_HALF_SIZE: f32 = 1.0;
+/// The radius of a food pellet's collider.
+const PELLET_RADIUS: f32 = 0.5;
+/// The energy a preditor gets from eating another critter, the same as for full critters.
+const PREY_ENERGY: u32 = 10;
+/// What lightweight critters fall with after a jump, the same as Avian's default gravity that full
+/// critters fall with.
+const GRAVITY: f32 = 9.81;
+
+const HERBIVORE_COLOR: Color = Color::srgb(0.45, 0.75, 0.35);
+const PREDITOR_COLOR: Color = Color::srgb(0.8, 0.25, 0.2);
+
+/// How much detail critters are simulated and drawn with. Each level picks its own.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+#[reflect(Resource)]
+pub enum CritterDetail {
+    /// Blueprints with full physics, however far they are from the camera.
+    Full,
+    /// Full detail near the camera and [`Lightweight`] further away. See [`super::lod`].
+    #[default]
+    Lod,
+    /// [`Lightweight`] critters, for levels with thousands of them.
+    Performance,
+}
+
+/// A critter that glides kinematically and is drawn as an instance of a shared mesh.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+pub struct Lightweight;
+
+/// The velocity a [`Lightweight`] critter glides with.
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component)]
+pub struct KinematicMovement {
+    pub velocity: Vec3,
+    /// How far above its hover height the critter is in the middle of a jump.
+    lift: f32,
+    vertical_speed: f32,
+}
+
+impl KinematicMovement {
+    pub fn new(velocity: Vec3) -> Self {
+        Self {
+            velocity,
+            ..default()
+        }
+    }
+
+    /// Jumps `height` high, unless the critter is already in the air.
+    pub fn jump(&mut self, height: f32) {
+        if self.lift == 0.0 {
+            self.vertical_speed = (2.0 * GRAVITY * height).sqrt();
+        }
+    }
+}
+
+/// The mesh and materials shared by all lightweight critters, so that each species is drawn in a
+/// single instanced batch.
+#[derive(Resource, Debug)]
+pub struct CrowdAssets {
+    mesh: Handle<Mesh>,
+    herbivore_material: Handle<StandardMaterial>,
+    preditor_material: Handle<StandardMaterial>,
+}
+
+impl CrowdAssets {
+    /// Makes a critter a lightweight herbivore.
+    pub fn herbivore(&self) -> impl Bundle {
+        (
+            Lightweight,
+            KinematicMovement::default(),
+            Mesh3d(self.mesh.clone()),
+            MeshMaterial3d(self.herbivore_material.clone()),
+        )
+    }
+
+    /// Makes a critter a lightweight preditor.
+    pub fn preditor(&self) -> impl Bundle {
+        (
+            Lightweight,
+            KinematicMovement::default(),
+            Mesh3d(self.mesh.clone()),
+            MeshMaterial3d(self.preditor_material.clone()),
+        )
+    }
+}
+
+impl FromWorld for CrowdAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mesh = world
+            .resource_mut::<Assets<Mesh>>()
+            .add(Capsule3d::new(0.5, HOVER_HEIGHT * 2.0 - 1.0));
+        let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+        Self {
+            mesh,
+            herbivore_material: materials.add(HERBIVORE_COLOR),
+            preditor_material: materials.add(PREDITOR_COLOR),
+        }
+    }
+}
+
+/// Lets one batch of lightweight critters look around and head for water, food or prey like full
+/// critters do, but in a straight line instead of along a path.
+///
+/// Critters that are only lightweight while they are far from the camera keep their
+/// [`Navigation`] and are steered by the same systems as full critters instead.
+fn sense(
+    mut critters: Query<
+        (
+            Entity,
+            &GlobalTransform,
+            &mut KinematicMovement,
+            &Speed,
+            &Nocturnality,
+            &Habitat,
+            &Hydration,
+            Has<Preditor>,
+        ),
+        (With<Lightweight>, Without<Navigation>, Without<Player>),
+    >,
+    pellet_grid: Res<SpatialGrid<FoodPellet>>,
+    herbivore_grid: Res<SpatialGrid<Herbivore>>,
+    water_tree: Res<KDTree3<WaterSource>>,
+    cycle: Res<DayCycle>,
+    biome_map: Res<BiomeMap>,
+    mut batch: Local<u32>,
+) {
+    *batch = (*batch + 1) % SENSING_BATCHES;
+    let batch = *batch;
+    let daylight = cycle.daylight();
+    critters.par_iter_mut().for_each(
+        |(
+            entity,
+            transform,
+            mut movement,
+            speed,
+            nocturnality,
+            habitat,
+            hydration,
+            is_preditor,
+        )| {
+            if entity.index() % SENSING_BATCHES != batch {
+                return;
+            }
+            let translation = transform.translation();
+            let biome = biome_map.biome_at(translation.xz());
+            let sight_range = cycle.sight_range() * biome.visibility();
+            // Thirsty critters go for water before food.
+            let water = water_tree
+                .nearest_neighbour(translation)
+                .map(|(pos, _)| pos)
+                .filter(|pos| hydration.is_thirsty() && pos.distance(translation) <= sight_range);
+            let food = || {
+                let food = if is_preditor {
+                    herbivore_grid.nearest_within(translation, sight_range)
+                } else {
+                    pellet_grid.nearest_within(translation, sight_range)
+                };
+                food.map(|(pos, _)| pos)
+            };
+            let direction = match water.or_else(food) {
+                Some(target) => (target - translation).with_y(0.0),
+                None => {
+                    let mut rng = thread_rng();
+                    Vec3::new(rng.gen_range(-1.0..1.0), 0.0, rng.gen_range(-1.0..1.0))
+                }
+            };
+            let pace = nocturnality.pace(daylight) * habitat.walk_speed(biome);
+            movement.velocity = direction.normalize_or_zero() * WALK_SPEED * speed.0 * pace;
+            if thread_rng().gen_bool(JUMP_CHANCE) {
+                movement.jump(JUMP_HEIGHT);
+            }
+        },
+    );
+}
+
+/// Moves lightweight critters along the ground, through lakes along their bottom like full
+/// critters. Without a heightmap to follow they keep their height, apart from jumping.
+fn glide(
+    mut critters: Query<(&mut Transform, &mut KinematicMovement), With<Lightweight>>,
+    nav_grid: Option<Res<NavGrid>>,
+    heightmap: Option<Res<Heightmap>>,
+    time: Res<Time>,
+) {
+    let delta = time.delta_secs();
+    critters
+        .par_iter_mut()
+        .for_each(|(mut transform, mut movement)| {
+            if movement.velocity == Vec3::ZERO && movement.vertical_speed == 0.0 {
+                return;
+            }
+            let hover_height = transform.translation.y - movement.lift;
+            if movement.vertical_speed != 0.0 {
+                movement.vertical_speed -= GRAVITY * delta;
+                movement.lift = (movement.lift + movement.vertical_speed * delta).max(0.0);
+                if movement.lift == 0.0 {
+                    movement.vertical_speed = 0.0;
+                }
+            }
+            let next = transform.translation + movement.velocity * delta;
+            // Obstacles and the edge of the level stop them until they pick another heading.
+            if nav_grid
+                .as_ref()
+                .is_some_and(|nav_grid| !nav_grid.is_walkable(next.xz()))
+            {
+                movement.velocity = Vec3::ZERO;
+            } else {
+                transform.translation = next;
+            }
+            let hover_height = heightmap.as_ref().map_or(hover_height, |heightmap| {
+                heightmap.height_at(transform.translation.xz()) + HOVER_HEIGHT
+            });
+            transform.translation.y = hover_height + movement.lift;
+            if movement.velocity != Vec3::ZERO {
+                transform.look_to(movement.velocity, Vec3::Y);
+            }
+        });
+}
+
+/// Lets lightweight critters eat what they would be touching if they had colliders: herbivores eat
+/// food pellets, and preditors eat any other critter, like full critters do.
+fn eat_within_reach(
+    mut commands: Commands,
+    mut eaters: Query<
+        (Entity, &GlobalTransform, &mut Energy, Has<Preditor>),
+        (With<Lightweight>, Without<Eaten>),
+    >,
+    pellet_grid: Res<SpatialGrid<FoodPellet>>,
+    herbivore_grid: Res<SpatialGrid<Herbivore>>,
+    preditor_grid: Res<SpatialGrid<Preditor>>,
+    plants: Query<Option<&PlantKind>, With<FoodPellet>>,
+    already_eaten: Query<(), With<Eaten>>,
+    mut ate: EventWriter<CritterAte>,
+    mut died: EventWriter<CritterDied>,
+) {
+    // The grids only find out that something was eaten next frame, and the markers only show up in
+    // queries once this system is done.
+    let mut eaten = HashSet::new();
+    for (eater, transform, mut energy, is_preditor) in &mut eaters {
+        if eaten.contains(&eater) {
+            continue;
+        }
+        let translation = transform.translation();
+        let food = if is_preditor {
+            // Two critters touch once their cubes do.
+            let reach = 2.0 * CRITTER_HALF_SIZE;
+            herbivore_grid
+                .within_distance(translation, reach)
+                .chain(preditor_grid.within_distance(translation, reach))
+                .filter(|(_, prey)| {
+                    *prey != eater && !eaten.contains(prey) && !already_eaten.contains(*prey)
+                })
+                .min_by(|(a, _), (b, _)| {
+                    a.distance_squared(translation)
+                        .total_cmp(&b.distance_squared(translation))
+                })
+        } else {
+            // Pellets lie on the ground, below the middle of the critter's cube, so they touch once
+            // the pellet reaches its side.
+            let pellet_height = translation - Vec3::Y * (HOVER_HEIGHT - PELLET_RADIUS);
+            pellet_grid
+                .nearest_within(pellet_height, CRITTER_HALF_SIZE + PELLET_RADIUS)
+                .filter(|(_, pellet)| !eaten.contains(pellet) && !already_eaten.contains(*pellet))
+        };
+        let Some((position, food)) = food else {
+            continue;
+        };
+        eaten.insert(food);
+        commands.entity(food).insert(Eaten);
+        if is_preditor {
+            energy.0 += PREY_ENERGY;
+            died.send(CritterDied {
+                critter: food,
+                translation: position,
+                cause: DeathCause::Eaten,
+            });
+        } else {
+            let plant = plants.get(food).ok().flatten();
+            energy.0 += plant.map_or(1, |plant| plant.nutrition());
+        }
+        ate.send(CritterAte { eater });
+    }
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct PerformanceStats;
+
+/// Shows the frame rate and critter count in performance mode.
+fn spawn_performance_stats(mut commands: Commands, detail: Res<CritterDetail>) {
+    if *detail != CritterDetail::Performance {
+        return;
+    }
+    commands
+        .spawn((
+            Name::new("Performance stats"),
+            Node {
+                position_type: PositionType::Absolute,
+                right: Px(10.0),
+                // Below the season HUD.
+                top: Px(70.0),
+                ..default()
+            },
+            StateScoped(Screen::Gameplay),
+        ))
+        .with_children(|children| {
+            children.label("").insert(PerformanceStats);
+        });
+}
+
+fn update_performance_stats(
+    diagnostics: Res<DiagnosticsStore>,
+    critters: Query<(), Or<(With<Herbivore>, With<Preditor>)>>,
+    mut stats: Query<&mut Text, With<PerformanceStats>>,
+) {
+    let smoothed = |path| {
+        diagnostics
+            .get(path)
+            .and_then(|diagnostic| diagnostic.smoothed())
+            .unwrap_or_default()
+    };
+    let fps = smoothed(&FrameTimeDiagnosticsPlugin::FPS);
+    let frame_time = smoothed(&FrameTimeDiagnosticsPlugin::FRAME_TIME);
+    let critters = critters.iter().len();
+    for mut text in &mut stats {
+        text.0 = format!("{fps:.0} FPS ({frame_time:.1} ms)\nCritters: {critters}");
+    }
+}
@@ -0,0 +1,136 @@
+//! The day/night cycle: the sun moves across the sky, the ambient light dims at night, and
+//! critters and plants react to how much daylight there is.
+//!
+//! The level's directional lights are taken over as the sun once they spawn.
+
+use std::f32::consts::{FRAC_PI_2, TAU};
+
+use bevy::prelude::*;
+
+use crate::{game::scenario::Scenario, screens::Screen, AppSet};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<DayCycle>();
+    app.register_type::<Sun>();
+    app.init_resource::<DayCycle>();
+    app.add_systems(OnEnter(Screen::Gameplay), (start_day, save_ambient_light));
+    app.add_systems(OnExit(Screen::Gameplay), restore_ambient_light);
+    app.add_systems(
+        Update,
+        (
+            advance_time_of_day.in_set(AppSet::TickTimers),
+            (add_sun, move_sun, dim_ambient_light)
+                .chain()
+                .in_set(AppSet::Update),
+        )
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// The time of day a run starts at: early morning.
+const START_TIME_OF_DAY: f32 = 0.3;
+/// How far below the horizon the sun can be before it is fully dark.
+const TWILIGHT_HEIGHT: f32 = 0.1;
+/// How far above the horizon the sun has to be for full daylight.
+const FULL_DAYLIGHT_HEIGHT: f32 = 0.3;
+
+const DAY_AMBIENT_COLOR: Color = Color::WHITE;
+const NIGHT_AMBIENT_COLOR: Color = Color::srgb(0.45, 0.55, 1.0);
+/// Bevy's default ambient brightness.
+const DAY_AMBIENT_BRIGHTNESS: f32 = 80.0;
+const NIGHT_AMBIENT_BRIGHTNESS: f32 = 15.0;
+
+/// How far critters can see food and prey in full daylight...
+const DAY_SIGHT_RANGE: f32 = 80.0;
+/// ...and in the middle of the night.
+const NIGHT_SIGHT_RANGE: f32 = 25.0;
+
+#[derive(Resource, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct DayCycle {
+    /// How much of the day has passed, between 0 and 1. Midnight is at 0 and noon at 0.5.
+    pub time_of_day: f32,
+}
+
+impl Default for DayCycle {
+    fn default() -> Self {
+        Self {
+            time_of_day: START_TIME_OF_DAY,
+        }
+    }
+}
+
+impl DayCycle {
+    /// Height of the sun above the horizon, between -1 at midnight and 1 at noon.
+    pub fn sun_height(&self) -> f32 {
+        -(self.time_of_day * TAU).cos()
+    }
+
+    /// How bright it is, between 0 at night and 1 during the day, with smooth twilight between.
+    pub fn daylight(&self) -> f32 {
+        ((self.sun_height() + TWILIGHT_HEIGHT) / (FULL_DAYLIGHT_HEIGHT + TWILIGHT_HEIGHT))
+            .clamp(0.0, 1.0)
+    }
+
+    /// How far critters can see with the current daylight.
+    pub fn sight_range(&self) -> f32 {
+        NIGHT_SIGHT_RANGE.lerp(DAY_SIGHT_RANGE, self.daylight())
+    }
+}
+
+/// A directional light that follows the time of day.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+struct Sun {
+    /// The light's illuminance at noon, as set in the level.
+    noon_illuminance: f32,
+}
+
+fn start_day(mut commands: Commands) {
+    commands.insert_resource(DayCycle::default());
+}
+
+fn advance_time_of_day(time: Res<Time>, scenario: Res<Scenario>, mut cycle: ResMut<DayCycle>) {
+    let days = time.delta_secs() / scenario.day_length.as_secs_f32();
+    cycle.time_of_day = (cycle.time_of_day + days).fract();
+}
+
+fn add_sun(mut commands: Commands, lights: Query<(Entity, &DirectionalLight), Without<Sun>>) {
+    for (entity, light) in &lights {
+        commands.entity(entity).insert(Sun {
+            noon_illuminance: light.illuminance,
+        });
+    }
+}
+
+fn move_sun(cycle: Res<DayCycle>, mut suns: Query<(&Sun, &mut DirectionalLight, &mut Transform)>) {
+    // Shining straight up at midnight, turning around the Z axis to shine straight down at noon.
+    let rotation =
+        Quat::from_rotation_z(cycle.time_of_day * TAU) * Quat::from_rotation_x(FRAC_PI_2);
+    let daylight = cycle.daylight();
+    for (sun, mut light, mut transform) in &mut suns {
+        transform.rotation = rotation;
+        light.illuminance = sun.noon_illuminance * daylight;
+    }
+}
+
+/// The ambient light from before gameplay, so that the other screens don't stay dark after a night.
+#[derive(Resource)]
+struct OriginalAmbientLight(AmbientLight);
+
+fn save_ambient_light(mut commands: Commands, ambient_light: Res<AmbientLight>) {
+    commands.insert_resource(OriginalAmbientLight(ambient_light.clone()));
+}
+
+fn restore_ambient_light(mut commands: Commands, original: Option<Res<OriginalAmbientLight>>) {
+    if let Some(original) = original {
+        commands.insert_resource(original.0.clone());
+        commands.remove_resource::<OriginalAmbientLight>();
+    }
+}
+
+fn dim_ambient_light(cycle: Res<DayCycle>, mut ambient_light: ResMut<AmbientLight>) {
+    let daylight = cycle.daylight();
+    ambient_light.color = NIGHT_AMBIENT_COLOR.mix(&DAY_AMBIENT_COLOR, daylight);
+    ambient_light.brightness = NIGHT_AMBIENT_BRIGHTNESS.lerp(DAY_AMBIENT_BRIGHTNESS, daylight);
+}
@@ -0,0 +1,239 @@
+//! Food pellets raining down on the level.
+//!
+//! Food only falls on [`FoodZone`]s. Generated terrain spawns them directly, and the handcrafted
+//! world gets one per floor plate once the plate's extents are known. Each zone gets a share of the
+//! rain according to its [`Biome`] and [`FoodWeight`].
+//!
+//! New food tends to grow close to existing food, like plants spreading from their neighbours,
+//! but never too close and never too much in one place. That keeps the food patchy without
+//! piling it up, and is checked against the [`SpatialGrid<FoodPellet>`] instead of every pellet.
+
+use std::{f32::consts::TAU, time::Duration};
+
+use bevy::{prelude::*, render::primitives::Aabb, time::common_conditions::on_timer};
+use rand::{distributions::WeightedIndex, prelude::*};
+
+use crate::{
+    game::{
+        biomes::Biome,
+        critters::FoodPellet,
+        day_cycle::DayCycle,
+        level::{floor_plate_extents, FloorPlate},
+        seasons::Climate,
+        spatial::SpatialGrid,
+        terrain::Heightmap,
+    },
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<FoodZone>();
+    app.register_type::<FoodWeight>();
+    app.init_resource::<FoodZoneCache>();
+    app.add_systems(
+        Update,
+        (
+            zone_floor_plates,
+            cache_food_zones,
+            food_pellet_rain.run_if(on_timer(Duration::from_millis(10))),
+        )
+            .chain(),
+    );
+}
+
+/// No more food rains down while there are this many pellets.
+const MAX_PELLETS: usize = 1000;
+/// Food falls from this high above its zone.
+const RAIN_HEIGHT: f32 = 20.0;
+/// The chance that new food grows next to existing food rather than anywhere in its zone.
+const SPREAD_CHANCE: f64 = 0.7;
+/// How far away existing food can be for new food to grow next to it...
+const SPREAD_RADIUS: f32 = 15.0;
+/// ...and how far from it the new food grows.
+const SPREAD_DISTANCE: f32 = 4.0;
+/// No food grows closer than this to other food.
+const MIN_SPACING: f32 = 1.5;
+/// No food grows where there is already this much food within [`DENSITY_RADIUS`].
+const MAX_LOCAL_PELLETS: usize = 12;
+const DENSITY_RADIUS: f32 = 8.0;
+/// How long new pellets are remembered, so that they count towards the spacing and density while
+/// they are still falling towards the spot they were meant for.
+const RECENT_PELLET_MEMORY: Duration = Duration::from_secs(3);
+
+/// An axis-aligned area that food pellets rain down on, centered on its transform.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+pub struct FoodZone {
+    pub half_size: Vec2,
+}
+
+/// How much of the food rain a food zone or floor plate gets compared to others in the same biome.
+/// Zones without one have a weight of 1.
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[reflect(Component)]
+pub struct FoodWeight(pub f32);
+
+/// The food zones with their extents in world space and their share of the rain, kept up to date
+/// as zones come and go.
+#[derive(Resource, Debug, Default)]
+pub struct FoodZoneCache {
+    zones: Vec<(Vec3, Vec2)>,
+    weights: Option<WeightedIndex<f32>>,
+}
+
+impl FoodZoneCache {
+    /// Caches zones given as their center, half size and weight.
+    pub fn from_zones(zones: impl IntoIterator<Item = (Vec3, Vec2, f32)>) -> Self {
+        let (zones, weights): (Vec<_>, Vec<_>) = zones
+            .into_iter()
+            .map(|(center, half_size, weight)| ((center, half_size), weight))
+            .unzip();
+        Self {
+            zones,
+            weights: WeightedIndex::new(weights).ok(),
+        }
+    }
+
+    /// A zone picked by weight, as its center and half size.
+    fn random_zone(&self, rng: &mut impl Rng) -> Option<(Vec3, Vec2)> {
+        let weights = self.weights.as_ref()?;
+        Some(self.zones[weights.sample(rng)])
+    }
+
+    /// A random point in a zone picked by weight, at the height of the zone's center.
+    pub fn random_point(&self, rng: &mut impl Rng) -> Option<Vec3> {
+        let (center, half_size) = self.random_zone(rng)?;
+        Some(
+            center
+                + Vec3::new(
+                    rng.gen_range(-half_size.x..=half_size.x),
+                    0.0,
+                    rng.gen_range(-half_size.y..=half_size.y),
+                ),
+        )
+    }
+}
+
+/// Marks a floor plate that has its [`FoodZone`].
+#[derive(Component)]
+struct Zoned;
+
+/// Gives each floor plate of the handcrafted world a food zone as soon as its extents are known.
+fn zone_floor_plates(
+    mut commands: Commands,
+    floor_plates: Query<
+        (Entity, Option<&Biome>, Option<&FoodWeight>),
+        (With<FloorPlate>, Without<Zoned>),
+    >,
+    children: Query<&Children>,
+    bounds: Query<(&GlobalTransform, &Aabb)>,
+) {
+    for (entity, biome, weight) in &floor_plates {
+        let Some((center, half_size)) = floor_plate_extents(entity, &children, &bounds) else {
+            continue;
+        };
+        let mut zone = commands.spawn((
+            Name::new("Food zone"),
+            FoodZone { half_size },
+            biome.copied().unwrap_or_default(),
+            Transform::from_translation(center),
+            StateScoped(Screen::Gameplay),
+        ));
+        if let Some(weight) = weight {
+            zone.insert(*weight);
+        }
+        commands.entity(entity).insert(Zoned);
+    }
+}
+
+fn cache_food_zones(
+    mut cache: ResMut<FoodZoneCache>,
+    // Zones are cached once their transforms have propagated.
+    changed: Query<(), (With<FoodZone>, Changed<GlobalTransform>)>,
+    mut removed: RemovedComponents<FoodZone>,
+    zones: Query<(
+        &GlobalTransform,
+        &FoodZone,
+        Option<&Biome>,
+        Option<&FoodWeight>,
+    )>,
+) {
+    let removed = removed.read().count() > 0;
+    if changed.is_empty() && !removed {
+        return;
+    }
+    *cache = FoodZoneCache::from_zones(zones.iter().map(|(transform, zone, biome, weight)| {
+        // Lush biomes get more of the rain than barren ones.
+        let density = biome.copied().unwrap_or_default().food_density();
+        (
+            transform.translation(),
+            zone.half_size,
+            density * weight.map_or(1.0, |weight| weight.0),
+        )
+    }));
+}
+
+fn food_pellet_rain(
+    mut commands: Commands,
+    pellet_grid: Res<SpatialGrid<FoodPellet>>,
+    cache: Res<FoodZoneCache>,
+    cycle: Res<DayCycle>,
+    climate: Res<Climate>,
+    heightmap: Option<Res<Heightmap>>,
+    time: Res<Time>,
+    mut recent_pellets: Local<Vec<(Vec3, Duration)>>,
+) {
+    let now = time.elapsed();
+    recent_pellets.retain(|(_, spawned)| now - *spawned < RECENT_PELLET_MEMORY);
+    if pellet_grid.len() >= MAX_PELLETS {
+        return;
+    }
+    let mut rng = rand::thread_rng();
+    // Plants only grow in daylight, and slower at dawn and dusk and in winter.
+    if !rng.gen_bool((cycle.daylight() * climate.food_production()).into()) {
+        return;
+    }
+    let Some((center, half_size)) = cache.random_zone(&mut rng) else {
+        return;
+    };
+    let in_zone = |point: Vec3| (point.xz() - center.xz()).abs().cmple(half_size).all();
+
+    let mut location = center
+        + Vec3::new(
+            rng.gen_range(-half_size.x..=half_size.x),
+            0.0,
+            rng.gen_range(-half_size.y..=half_size.y),
+        );
+    if rng.gen_bool(SPREAD_CHANCE) {
+        if let Some((neighbour, _)) = pellet_grid.nearest_within(location, SPREAD_RADIUS) {
+            let offset = Vec2::from_angle(rng.gen_range(0.0..TAU))
+                * rng.gen_range(MIN_SPACING..SPREAD_DISTANCE);
+            let spread = Vec3::new(neighbour.x + offset.x, center.y, neighbour.z + offset.y);
+            if in_zone(spread) {
+                location = spread;
+            }
+        }
+    }
+
+    // Zones on terrain follow its slopes, which can rise well above the zone's center.
+    if let Some(heightmap) = &heightmap {
+        location.y = heightmap.height_at(location.xz());
+    }
+
+    let nearby = pellet_grid
+        .within_distance(location, DENSITY_RADIUS)
+        .map(|(pellet, _)| pellet)
+        .chain(recent_pellets.iter().map(|(pellet, _)| *pellet))
+        .map(|pellet| pellet.distance(location))
+        .filter(|&distance| distance <= DENSITY_RADIUS)
+        .collect::<Vec<_>>();
+    if nearby.len() >= MAX_LOCAL_PELLETS || nearby.iter().any(|&distance| distance < MIN_SPACING) {
+        return;
+    }
+
+    recent_pellets.push((location, now));
+    commands.spawn((
+        FoodPellet,
+        Transform::from_translation(location + Vec3::Y * RAIN_HEIGHT),
+    ));
+}
@@ -1,55 +1,333 @@
-use std::time::Duration;
-
 use rand::prelude::*;
 
 use bevy::{
+    gltf::Gltf,
     prelude::*,
-    time::common_conditions::on_timer,
+    render::primitives::Aabb,
 };
 use blenvy::*;
+use serde::{Deserialize, Serialize};
 
-use crate::game::critters::{FoodPellet, Herbivore, Preditor};
+use crate::{
+    asset_tracking::LoadResource,
+    game::{
+        biomes::{BiomeMap, WORLD_SIZE},
+        bounds::{spawn_bounds, BoundaryMode, WorldBounds},
+        critters::{FoodPellet, Herbivore, Preditor},
+        crowd::CritterDetail,
+        navigation::NavGrid,
+        save::SavedEcosystem,
+        terrain::{spawn_terrain, Heightmap},
+        water::spawn_ponds,
+        GameMode,
+    },
+    screens::Screen,
+};
 
 #[derive(Component, Reflect)]
 #[reflect(Component)]
 pub struct FloorPlate;
 
+/// The world-space center and XZ half extents of a floor plate, once its mesh has loaded and been
+/// placed.
+pub fn floor_plate_extents(
+    floor_plate: Entity,
+    children: &Query<&Children>,
+    bounds: &Query<(&GlobalTransform, &Aabb)>,
+) -> Option<(Vec3, Vec2)> {
+    let (transform, aabb) = children
+        .iter_descendants(floor_plate)
+        .find_map(|child| bounds.get(child).ok())?;
+    let center = transform.transform_point(aabb.center.into());
+    let half_extents = transform.affine().matrix3.abs() * aabb.half_extents;
+    Some((center, half_extents.xz()))
+}
+
+/// Seeds the placement of the level's initial critters and food pellets, so that a run can be
+/// restarted with the same layout. A new seed is rolled whenever a level spawns without one.
+#[derive(Resource, Debug, Clone, Copy, Reflect)]
+#[reflect(Resource)]
+pub struct SimulationSeed(pub u64);
+
+impl SimulationSeed {
+    fn random() -> Self {
+        Self(random())
+    }
+}
+
+/// A level that can be played, as listed on the level select screen.
+#[derive(Debug, Clone)]
+pub struct LevelInfo {
+    /// Identifies the level in saves, so it must never change.
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Path to an image of the level, if there is one.
+    pub preview: Option<&'static str>,
+    /// The game mode the level is best played in.
+    pub recommended_mode: GameMode,
+    pub source: LevelSource,
+    /// What happens to critters and food pellets at the edges of the level.
+    pub boundary: BoundaryMode,
+    pub population: Population,
+    /// Levels with thousands of critters need [`CritterDetail::Performance`].
+    pub critter_detail: CritterDetail,
+}
+
+/// How many critters and food pellets a level starts with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Population {
+    pub herbivores: usize,
+    pub preditors: usize,
+    pub food_pellets: usize,
+}
+
+impl Default for Population {
+    fn default() -> Self {
+        Self {
+            herbivores: 30,
+            preditors: 3,
+            food_pellets: 200,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LevelSource {
+    /// A level authored in Blender and exported with blenvy, which fits in a square `size` units
+    /// across.
+    Blueprint { path: &'static str, size: f32 },
+    /// Terrain generated from the [`SimulationSeed`], `size` units across.
+    Terrain { size: f32 },
+}
+
+impl LevelSource {
+    /// How far across the level is.
+    pub fn size(self) -> f32 {
+        match self {
+            Self::Blueprint { size, .. } | Self::Terrain { size } => size,
+        }
+    }
+}
+
+/// All levels that can be played, in the order they are listed in.
+#[derive(Resource, Debug, Default)]
+pub struct LevelRegistry(Vec<LevelInfo>);
+
+impl LevelRegistry {
+    pub fn get(&self, id: &str) -> Option<&LevelInfo> {
+        self.0.iter().find(|level| level.id == id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &LevelInfo> {
+        self.0.iter()
+    }
+}
+
+pub trait AddLevel {
+    /// Adds a level to the [`LevelRegistry`].
+    fn add_level(&mut self, level: LevelInfo) -> &mut Self;
+}
+
+impl AddLevel for App {
+    fn add_level(&mut self, level: LevelInfo) -> &mut Self {
+        self.init_resource::<LevelRegistry>();
+        self.world_mut().resource_mut::<LevelRegistry>().0.push(level);
+        self
+    }
+}
+
+/// The id of the level gameplay takes place in. Chosen on the level select screen.
+#[derive(Resource, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Reflect)]
+#[reflect(Resource)]
+pub struct SelectedLevel(pub String);
+
+impl Default for SelectedLevel {
+    fn default() -> Self {
+        Self(WORLD_LEVEL.to_string())
+    }
+}
+
+/// The id of the level authored in Blender.
+const WORLD_LEVEL: &str = "world";
+/// Anything that falls this far below the ground has fallen out of the level.
+const KILL_HEIGHT: f32 = -50.0;
+
+/// The level and critter blueprints, preloaded so that gameplay starts without hitches and
+/// critters don't pop in after spawning.
+#[derive(Resource, Asset, Reflect, Clone)]
+pub struct LevelAssets {
+    #[dependency]
+    world: Handle<Gltf>,
+    #[dependency]
+    herbivore: Handle<Gltf>,
+    #[dependency]
+    preditor: Handle<Gltf>,
+    #[dependency]
+    food_pellet: Handle<Gltf>,
+}
+
+impl LevelAssets {
+    pub const PATH_WORLD: &'static str = "levels/World.glb";
+    pub const PATH_HERBIVORE: &'static str = "blueprints/Herbivore.glb";
+    pub const PATH_PREDITOR: &'static str = "blueprints/Preditor.glb";
+    pub const PATH_FOOD_PELLET: &'static str = "blueprints/FoodPellet.glb";
+}
+
+impl FromWorld for LevelAssets {
+    fn from_world(world: &mut World) -> Self {
+        let assets = world.resource::<AssetServer>();
+        Self {
+            world: assets.load(Self::PATH_WORLD),
+            herbivore: assets.load(Self::PATH_HERBIVORE),
+            preditor: assets.load(Self::PATH_PREDITOR),
+            food_pellet: assets.load(Self::PATH_FOOD_PELLET),
+        }
+    }
+}
+
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<FloorPlate>();
-    app.add_systems(Update, (
-        spawn_food_pellet,
-        food_pellet_rain.run_if(on_timer(Duration::from_millis(10))),
-    ));
+    app.register_type::<SelectedLevel>();
+    app.init_resource::<SelectedLevel>();
+    app.add_level(LevelInfo {
+        id: WORLD_LEVEL,
+        name: "Handcrafted world",
+        description: "The meadow the first critters were released in, built by hand.",
+        preview: None,
+        recommended_mode: GameMode::Sandbox,
+        source: LevelSource::Blueprint {
+            path: LevelAssets::PATH_WORLD,
+            size: WORLD_SIZE,
+        },
+        boundary: BoundaryMode::KillPlane,
+        population: Population::default(),
+        critter_detail: CritterDetail::Lod,
+    });
+    app.add_level(LevelInfo {
+        id: "small_island",
+        name: "Small island",
+        description: "Food and water are never far away, and neither are the predators.",
+        preview: None,
+        recommended_mode: GameMode::Survival,
+        source: LevelSource::Terrain { size: 240.0 },
+        // The hills around it are steep, but not steep enough for the smallest critters.
+        boundary: BoundaryMode::Walls,
+        population: Population::default(),
+        critter_detail: CritterDetail::Lod,
+    });
+    app.add_level(LevelInfo {
+        id: "large_island",
+        name: "Large island",
+        description: "Room for whole populations to spread out and adapt to every biome.",
+        preview: None,
+        recommended_mode: GameMode::Sandbox,
+        source: LevelSource::Terrain { size: 480.0 },
+        // Whatever swims out past the rim comes back ashore on the other side.
+        boundary: BoundaryMode::WrapAround,
+        population: Population::default(),
+        critter_detail: CritterDetail::Lod,
+    });
+    app.add_level(LevelInfo {
+        id: "benchmark",
+        name: "Benchmark",
+        description: "Ten thousand critters in performance mode, with the frame rate shown.",
+        preview: None,
+        recommended_mode: GameMode::Sandbox,
+        source: LevelSource::Terrain { size: 480.0 },
+        boundary: BoundaryMode::KillPlane,
+        population: Population {
+            herbivores: 9000,
+            preditors: 1000,
+            food_pellets: 1000,
+        },
+        critter_detail: CritterDetail::Performance,
+    });
+    app.load_resource::<LevelAssets>();
+    app.register_type::<SimulationSeed>();
+    // Leaving the game for good starts the next one with a fresh layout.
+    app.add_systems(OnEnter(Screen::Title), forget_seed);
+    app.add_systems(OnEnter(Screen::GameOver), forget_seed);
+    app.add_systems(Update, spawn_food_pellet);
 }
 
 /// A [`Command`] to spawn the level.
 /// Functions that accept only `&mut World` as their parameter implement [`Command`].
 /// We use this style when a command requires no configuration.
 pub fn spawn_level(world: &mut World) {
-    world.spawn((
-        BlueprintInfo::from_path("levels/World.glb"),
-        SpawnBlueprint,
-        HideUntilReady,
-        GameWorldTag,
-    ));
-
-    let mut rng = rand::thread_rng();
-    for _ in 0..30 {
-        let location = Vec3::new(rng.gen_range(-80.0..80.0), 2.0, rng.gen_range(-80.0..80.0));
+    let saved = world.remove_resource::<SavedEcosystem>();
+    if let Some(saved) = &saved {
+        world.insert_resource(SimulationSeed(saved.seed));
+        world.insert_resource(saved.level.clone());
+    }
+    let seed = *world.get_resource_or_insert_with(SimulationSeed::random);
+    let mut rng = StdRng::seed_from_u64(seed.0);
+
+    let registry = world.resource::<LevelRegistry>();
+    let selected = world.resource::<SelectedLevel>();
+    let level = registry
+        .get(&selected.0)
+        .unwrap_or_else(|| {
+            warn!("Unknown level {:?}, playing the first one instead", selected.0);
+            &registry.0[0]
+        })
+        .clone();
+    spawn_bounds(world, WorldBounds {
+        half_size: level.source.size() / 2.0,
+        kill_height: KILL_HEIGHT,
+        mode: level.boundary,
+    });
+    world.insert_resource(level.critter_detail);
+
+    let terrain = match level.source {
+        LevelSource::Blueprint { path, size } => {
+            // Painted by the floor plates once they load.
+            world.insert_resource(BiomeMap::new(size));
+            world.remove_resource::<Heightmap>();
+            world.insert_resource(NavGrid::new(size));
+            world.spawn((
+                BlueprintInfo::from_path(path),
+                SpawnBlueprint,
+                HideUntilReady,
+                GameWorldTag,
+                // Everything spawned with `AddToGameWorld` ends up as a descendant of the world,
+                // so this also cleans up all critters and food pellets.
+                StateScoped(Screen::Gameplay),
+            ));
+            spawn_ponds(world, &mut rng, 4, 80.0);
+            None
+        }
+        LevelSource::Terrain { size } => {
+            world.insert_resource(NavGrid::new(size));
+            Some(spawn_terrain(world, seed.0, size))
+        }
+    };
+
+    if let Some(saved) = saved {
+        saved.spawn(world);
+        return;
+    }
+
+    let random_location = |rng: &mut StdRng| match &terrain {
+        Some(terrain) => terrain.random_land_point(rng) + Vec3::Y * 2.0,
+        None => Vec3::new(rng.gen_range(-80.0..80.0), 2.0, rng.gen_range(-80.0..80.0)),
+    };
+    for _ in 0..level.population.herbivores {
+        let location = random_location(&mut rng);
         world.spawn((
             Herbivore,
             Transform::from_translation(location),
         ));
     }
-    for _ in 0..3 {
-        let location = Vec3::new(rng.gen_range(-80.0..80.0), 2.0, rng.gen_range(-80.0..80.0));
+    for _ in 0..level.population.preditors {
+        let location = random_location(&mut rng);
         world.spawn((
             Preditor,
             Transform::from_translation(location),
         ));
     }
-    for _ in 0..200 {
-        let location = Vec3::new(rng.gen_range(-80.0..80.0), 2.0, rng.gen_range(-80.0..80.0));
+    for _ in 0..level.population.food_pellets {
+        let location = random_location(&mut rng);
         world.spawn((
             FoodPellet,
             Transform::from_translation(location),
@@ -57,41 +335,20 @@ pub fn spawn_level(world: &mut World) {
     }
 }
 
+fn forget_seed(mut commands: Commands) {
+    commands.remove_resource::<SimulationSeed>();
+}
+
 fn spawn_food_pellet(
     mut commands: Commands,
     query: Query<Entity, Added<FoodPellet>>,
 ) {
     for entity in &query {
         commands.entity(entity).insert((
-            BlueprintInfo::from_path("blueprints/FoodPellet.glb"),
+            BlueprintInfo::from_path(LevelAssets::PATH_FOOD_PELLET),
             SpawnBlueprint,
             HideUntilReady,
             AddToGameWorld,
         ));
     }
 }
-
-fn food_pellet_rain(
-    mut commands: Commands,
-    existing_pellets: Query<Entity, With<FoodPellet>>,
-    floor_plates: Query<(Entity, &GlobalTransform), With<FloorPlate>>,
-    children: Query<&Children>,
-    aabs: Query<&bevy::render::primitives::Aabb>,
-) {
-    if existing_pellets.iter().count() > 1000 {
-        return
-    }
-    let mut rng = rand::thread_rng();
-    if let Some((entity, transform)) = floor_plates.iter().choose(&mut rng) {
-        for child in children.iter_descendants(entity) {
-            if let Ok(aab) = aabs.get(child) {
-                let location = transform.transform_point(Vec3::new(rng.gen_range(-aab.half_extents.x..aab.half_extents.x), 20.0, rng.gen_range(-aab.half_extents.z..aab.half_extents.z)) + Vec3::from(aab.center));
-                commands.spawn((
-                    FoodPellet,
-                    Transform::from_translation(location),
-                ));
-                return
-            }
-        }
-    }
-}
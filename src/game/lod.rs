@@ -0,0 +1,126 @@
+//! Level of detail for critters, in levels with [`CritterDetail::Lod`].
+//!
+//! Critters near the camera are full blueprints that walk with Tnua and Avian physics. Further away
+//! they turn [`Lightweight`]: their body turns kinematic and glides along the ground, and their
+//! blueprint is hidden behind the shared mesh of their species. They keep thinking the same way
+//! though, since the same systems steer them, and their metabolism, thirst and reproduction don't
+//! depend on their detail at all. Lightweight critters jump as often and as high as full ones, and
+//! eat the same food when it touches them. What they can't do is get pushed around.
+//!
+//! How far is far depends on how far the camera is zoomed out: critters around what the camera looks
+//! at are always in full detail. A critter carries its velocity over when it switches, and only
+//! switches back once it is well past the distance it switched at, so that critters at the boundary
+//! don't flicker.
+
+use std::time::Duration;
+
+use avian3d::prelude::*;
+use bevy::{prelude::*, time::common_conditions::on_timer};
+use bevy_tnua::prelude::*;
+use bevy_tnua_avian3d::TnuaAvian3dSensorShape;
+use blenvy::BlueprintInstanceReady;
+use smooth_bevy_cameras::LookTransform;
+
+use crate::game::{
+    critters::{Herbivore, Preditor},
+    crowd::{CritterDetail, CrowdAssets, KinematicMovement, Lightweight},
+    tnua, Player,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        update_critter_lod
+            .run_if(resource_equals(CritterDetail::Lod))
+            .run_if(on_timer(Duration::from_millis(250))),
+    );
+}
+
+/// Critters further than this beyond what the camera looks at turn lightweight...
+const FAR_MARGIN: f32 = 120.0;
+/// ...and come back to full detail once they are back within this.
+const NEAR_MARGIN: f32 = 100.0;
+
+fn update_critter_lod(
+    mut commands: Commands,
+    camera: Query<&LookTransform, With<Camera3d>>,
+    critters: Query<
+        (
+            Entity,
+            &GlobalTransform,
+            Option<&LinearVelocity>,
+            Option<&KinematicMovement>,
+            Option<&Children>,
+            Has<Preditor>,
+            Has<Player>,
+        ),
+        (
+            Or<(With<Herbivore>, With<Preditor>)>,
+            // Blueprints that are still spawning would overwrite their body.
+            With<BlueprintInstanceReady>,
+        ),
+    >,
+    mut visibilities: Query<&mut Visibility>,
+    crowd_assets: Res<CrowdAssets>,
+) {
+    let Ok(camera) = camera.get_single() else {
+        return;
+    };
+    let zoom = camera.eye.distance(camera.target);
+    for (entity, transform, velocity, movement, children, is_preditor, is_player) in &critters {
+        let distance = transform.translation().distance(camera.eye);
+        let is_lightweight = movement.is_some();
+        let margin = if is_lightweight {
+            NEAR_MARGIN
+        } else {
+            FAR_MARGIN
+        };
+        // The possessed critter stays in full detail, so that it can jump and push things around.
+        let far = !is_player && distance > zoom + margin;
+        if far == is_lightweight {
+            continue;
+        }
+
+        let mut critter = commands.entity(entity);
+        let blueprint_visibility = if far {
+            let velocity = velocity.map_or(Vec3::ZERO, |velocity| velocity.0.with_y(0.0));
+            critter
+                .remove_with_requires::<(TnuaController, TnuaAvian3dSensorShape)>()
+                .insert((RigidBody::Kinematic, LinearVelocity::ZERO));
+            if is_preditor {
+                critter.insert(crowd_assets.preditor());
+            } else {
+                critter.insert(crowd_assets.herbivore());
+            }
+            critter.insert(KinematicMovement::new(velocity));
+            Visibility::Hidden
+        } else {
+            let velocity = movement.map_or(Vec3::ZERO, |movement| movement.velocity);
+            // Fed right away, so that the critter doesn't drop until it is next steered.
+            let mut controller = TnuaController::default();
+            controller.basis(TnuaBuiltinWalk {
+                desired_velocity: velocity,
+                float_height: 1.5,
+                ..Default::default()
+            });
+            critter
+                .remove::<(
+                    Lightweight,
+                    KinematicMovement,
+                    Mesh3d,
+                    MeshMaterial3d<StandardMaterial>,
+                )>()
+                .insert((
+                    RigidBody::Dynamic,
+                    LinearVelocity(velocity),
+                    tnua(controller),
+                ));
+            Visibility::Inherited
+        };
+        for child in children.into_iter().flatten() {
+            if let Ok(mut visibility) = visibilities.get_mut(*child) {
+                *visibility = blueprint_visibility;
+            }
+        }
+    }
+}
@@ -3,16 +3,45 @@
 //! Feel free to change the logic found here if you feel like tinkering around
 //! to get a feeling for the template.
 
+use std::collections::BTreeMap;
+
 use avian3d::prelude::*;
-use bevy::prelude::*;
+use bevy::{prelude::*, window::PrimaryWindow};
 use bevy_tnua::prelude::*;
 use bevy_tnua_avian3d::*;
 use leafwing_input_manager::prelude::*;
-use smooth_bevy_cameras::controllers::orbit::{OrbitCameraBundle, OrbitCameraController};
+use serde::{Deserialize, Serialize};
+use smooth_bevy_cameras::controllers::orbit::{
+    ControlEvent, OrbitCameraBundle, OrbitCameraController,
+};
+
+use crate::{asset_tracking::ResourceGroup, persistence, screens::PauseMenu};
+use biomes::{BiomeMap, Habitat};
+use critters::{Speed, JUMP_HEIGHT};
+use crowd::KinematicMovement;
 
+pub mod biomes;
+pub mod bounds;
+mod critter_sounds;
 pub mod critters;
+pub mod crowd;
+pub mod day_cycle;
+pub mod food;
 pub mod level;
+pub mod lod;
+mod music;
+pub mod navigation;
+mod possession;
+mod sandbox;
+pub mod save;
+pub mod scenario;
+pub mod seasons;
+pub mod spatial;
+pub mod survival;
+pub mod terrain;
+pub mod water;
 
+/// Marks the critter currently controlled by the player instead of the AI.
 #[derive(Component, Reflect)]
 #[reflect(Component)]
 struct Player;
@@ -25,7 +54,21 @@ struct PlayerCamera;
 #[reflect(Component)]
 struct NeedsTnua;
 
-#[derive(Actionlike, PartialEq, Eq, Clone, Copy, Hash, Debug, Reflect)]
+/// Actions of the possessed critter. Movement is relative to the direction the camera is facing.
+#[derive(
+    Actionlike,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Clone,
+    Copy,
+    Hash,
+    Debug,
+    Reflect,
+    Serialize,
+    Deserialize,
+)]
 pub enum PlayerAction {
     Up,
     Down,
@@ -34,30 +77,208 @@ pub enum PlayerAction {
     Jump,
 }
 
+impl PlayerAction {
+    pub const ALL: [Self; 5] = [Self::Up, Self::Down, Self::Left, Self::Right, Self::Jump];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Up => "Move forward",
+            Self::Down => "Move back",
+            Self::Left => "Move left",
+            Self::Right => "Move right",
+            Self::Jump => "Jump",
+        }
+    }
+}
+
+/// The keyboard keys bound to each [`PlayerAction`], in up to [`KeyBindings::SLOTS`] slots each.
+/// These can be rebound on the controls screen and are persisted between runs.
+#[derive(Resource, Debug, Clone, Serialize, Deserialize)]
+pub struct KeyBindings(pub BTreeMap<PlayerAction, Vec<KeyCode>>);
+
+impl KeyBindings {
+    pub const FILE_NAME: &'static str = "key_bindings.ron";
+    /// How many keys each action can be bound to.
+    pub const SLOTS: usize = 2;
+
+    /// The key in one of an action's slots, if there is one.
+    pub fn get(&self, action: PlayerAction, slot: usize) -> Option<KeyCode> {
+        self.0.get(&action)?.get(slot).copied()
+    }
+
+    /// Binds `key` to one slot of `action`, keeping the action's other keys. If another slot
+    /// already has the key, it gets the key this slot had instead, so that no key does two things.
+    pub fn bind(&mut self, action: PlayerAction, slot: usize, key: KeyCode) {
+        let replaced = self.get(action, slot);
+        for keys in self.0.values_mut() {
+            if let Some(index) = keys.iter().position(|&bound| bound == key) {
+                match replaced {
+                    Some(replaced) => keys[index] = replaced,
+                    None => {
+                        keys.remove(index);
+                    }
+                }
+            }
+        }
+        let keys = self.0.entry(action).or_default();
+        if slot < keys.len() {
+            keys[slot] = key;
+        } else {
+            keys.push(key);
+        }
+    }
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self(BTreeMap::from([
+            (PlayerAction::Up, vec![KeyCode::KeyW, KeyCode::ArrowUp]),
+            (PlayerAction::Down, vec![KeyCode::KeyS, KeyCode::ArrowDown]),
+            (PlayerAction::Left, vec![KeyCode::KeyA, KeyCode::ArrowLeft]),
+            (
+                PlayerAction::Right,
+                vec![KeyCode::KeyD, KeyCode::ArrowRight],
+            ),
+            (PlayerAction::Jump, vec![KeyCode::Space]),
+        ]))
+    }
+}
+
+/// The gamepad button bound to each [`PlayerAction`]. These can be rebound on the controls screen
+/// and are persisted between runs. The left stick always moves as well.
+#[derive(Resource, Debug, Clone, Serialize, Deserialize)]
+pub struct GamepadBindings(pub BTreeMap<PlayerAction, GamepadButton>);
+
+impl GamepadBindings {
+    pub const FILE_NAME: &'static str = "gamepad_bindings.ron";
+
+    /// Binds `button` to `action`. If another action already has the button, it gets the button
+    /// `action` had instead.
+    pub fn bind(&mut self, action: PlayerAction, button: GamepadButton) {
+        let replaced = self.0.get(&action).copied();
+        let other = self
+            .0
+            .iter()
+            .find(|&(_, &bound)| bound == button)
+            .map(|(&other, _)| other);
+        if let Some(other) = other {
+            match replaced {
+                Some(replaced) => self.0.insert(other, replaced),
+                None => self.0.remove(&other),
+            };
+        }
+        self.0.insert(action, button);
+    }
+}
+
+impl Default for GamepadBindings {
+    fn default() -> Self {
+        Self(BTreeMap::from([
+            (PlayerAction::Up, GamepadButton::DPadUp),
+            (PlayerAction::Down, GamepadButton::DPadDown),
+            (PlayerAction::Left, GamepadButton::DPadLeft),
+            (PlayerAction::Right, GamepadButton::DPadRight),
+            (PlayerAction::Jump, GamepadButton::South),
+        ]))
+    }
+}
+
+fn input_map(
+    key_bindings: &KeyBindings,
+    gamepad_bindings: &GamepadBindings,
+) -> InputMap<PlayerAction> {
+    let mut input_map = InputMap::default();
+    for (action, keys) in &key_bindings.0 {
+        for key in keys {
+            input_map.insert(*action, *key);
+        }
+    }
+    for (action, button) in &gamepad_bindings.0 {
+        input_map.insert(*action, *button);
+    }
+    input_map
+        .insert(PlayerAction::Up, GamepadControlDirection::LEFT_UP)
+        .insert(PlayerAction::Down, GamepadControlDirection::LEFT_DOWN)
+        .insert(PlayerAction::Left, GamepadControlDirection::LEFT_LEFT)
+        .insert(PlayerAction::Right, GamepadControlDirection::LEFT_RIGHT);
+    input_map
+}
+
+/// The input map given to a critter when it gets possessed, built from [`KeyBindings`] and
+/// [`GamepadBindings`].
 #[derive(Resource)]
 struct PlayerInputMap(InputMap<PlayerAction>);
 
+/// How the next gameplay session is played. Chosen on the level select screen.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+#[reflect(Resource)]
+pub enum GameMode {
+    /// Watch the ecosystem and possess any critter at will.
+    #[default]
+    Sandbox,
+    /// Play as a single critter that has to survive and found a dynasty.
+    Survival,
+}
+
+/// Resources that are only needed once gameplay starts. They load in the background while the
+/// title screen is shown.
+pub const GAMEPLAY_RESOURCES: ResourceGroup = ResourceGroup("gameplay");
+
 pub(super) fn plugin(app: &mut App) {
-    app.add_plugins((level::plugin, critters::plugin));
+    // Split up, since plugin tuples can't have more than 15 members.
+    app.add_plugins((
+        level::plugin,
+        biomes::plugin,
+        bounds::plugin,
+        critters::plugin,
+        critter_sounds::plugin,
+        crowd::plugin,
+        day_cycle::plugin,
+        food::plugin,
+    ));
+    app.add_plugins((
+        lod::plugin,
+        music::plugin,
+        navigation::plugin,
+        possession::plugin,
+        sandbox::plugin,
+        scenario::plugin,
+        seasons::plugin,
+        survival::plugin,
+        terrain::plugin,
+        water::plugin,
+    ));
+    app.register_type::<GameMode>();
+    app.init_resource::<GameMode>();
     app.register_type::<Player>();
     //app.register_type::<PlayerCamera>();
     app.register_type::<NeedsTnua>();
     app.add_systems(Startup, setup_camera);
+    app.add_systems(
+        Update,
+        orbit_camera_with_gamepad.run_if(in_state(PauseMenu::Closed)),
+    );
     app.add_systems(Update, setup_tnua);
     app.add_systems(
         FixedUpdate,
         apply_controls.in_set(TnuaUserControlsSystemSet),
     );
-    app.insert_resource(PlayerInputMap(InputMap::new([
-        (PlayerAction::Up, KeyCode::ArrowUp),
-        (PlayerAction::Down, KeyCode::ArrowDown),
-        (PlayerAction::Left, KeyCode::ArrowLeft),
-        (PlayerAction::Right, KeyCode::ArrowRight),
-        (PlayerAction::Jump, KeyCode::Space),
-    ])));
+    let key_bindings = persistence::load::<KeyBindings>(KeyBindings::FILE_NAME);
+    let gamepad_bindings = persistence::load::<GamepadBindings>(GamepadBindings::FILE_NAME);
+    app.insert_resource(PlayerInputMap(input_map(&key_bindings, &gamepad_bindings)));
+    app.insert_resource(key_bindings);
+    app.insert_resource(gamepad_bindings);
+    app.add_systems(
+        Update,
+        update_input_maps
+            .run_if(resource_changed::<KeyBindings>.or(resource_changed::<GamepadBindings>)),
+    );
     app.add_plugins(InputManagerPlugin::<PlayerAction>::default());
 }
 
+/// Distance between the camera's ears for spatial audio.
+const CAMERA_EAR_GAP: f32 = 4.0;
+
 /// Sets up orbital view which can be used like this:
 ///
 /// CTRL + mouse drag: Rotate camera
@@ -73,65 +294,165 @@ fn setup_camera(mut commands: Commands) {
     };
     commands
         .spawn(Camera3d::default())
-        .insert(OrbitCameraBundle::new(controller, eye, target, Vec3::Y));
+        .insert(OrbitCameraBundle::new(controller, eye, target, Vec3::Y))
+        // Critter sounds are heard from the camera's point of view.
+        .insert(SpatialListener::new(CAMERA_EAR_GAP));
+}
+
+/// How fast the camera orbits with the right stick fully tilted.
+const GAMEPAD_ORBIT_SENSITIVITY: f32 = 2.0;
+/// How fast the triggers zoom the camera.
+const GAMEPAD_ZOOM_SPEED: f32 = 1.5;
+const GAMEPAD_STICK_DEADZONE: f32 = 0.15;
+
+/// Gamepad equivalent of the orbit camera's mouse controls:
+///
+/// Right stick: Rotate camera
+/// Left trigger / right trigger: Zoom out / in
+fn orbit_camera_with_gamepad(
+    gamepads: Query<&Gamepad>,
+    time: Res<Time<Real>>,
+    mut events: EventWriter<ControlEvent>,
+) {
+    for gamepad in &gamepads {
+        let stick = gamepad.right_stick();
+        if stick.length() > GAMEPAD_STICK_DEADZONE {
+            // Stick Y points up, while mouse motion (which the orbit controller expects) points down.
+            events.send(ControlEvent::Orbit(
+                stick * Vec2::new(1.0, -1.0) * GAMEPAD_ORBIT_SENSITIVITY,
+            ));
+        }
+
+        let zoom = gamepad.get(GamepadButton::LeftTrigger2).unwrap_or(0.0)
+            - gamepad.get(GamepadButton::RightTrigger2).unwrap_or(0.0);
+        if zoom != 0.0 {
+            events.send(ControlEvent::Zoom(
+                1.0 + zoom * GAMEPAD_ZOOM_SPEED * time.delta_secs(),
+            ));
+        }
+    }
 }
 
 fn setup_tnua(
     mut commands: Commands,
     query: Query<Entity, (With<NeedsTnua>, Without<TnuaController>)>,
-    input_map: Res<PlayerInputMap>,
 ) {
     for entity in &query {
         commands
             .entity(entity)
-            .insert((
-                TnuaController::default(),
-                TnuaAvian3dSensorShape(Collider::cylinder(0.49, 0.0)),
-                LockedAxes::ROTATION_LOCKED,
-                InputManagerBundle::with_map(input_map.0.clone()),
-            ))
+            .insert(tnua(TnuaController::default()))
             .remove::<NeedsTnua>();
     }
 }
 
+/// What a critter needs to walk with Tnua, starting out with `controller`.
+fn tnua(controller: TnuaController) -> impl Bundle {
+    (
+        controller,
+        TnuaAvian3dSensorShape(Collider::cylinder(0.49, 0.0)),
+        LockedAxes::ROTATION_LOCKED,
+    )
+}
+
+/// Casts a ray from the 3D camera through the cursor, if the cursor is inside the window.
+fn cursor_ray(
+    window: &Query<&Window, With<PrimaryWindow>>,
+    camera: &Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+) -> Option<Ray3d> {
+    let cursor = window.get_single().ok()?.cursor_position()?;
+    let (camera, camera_transform) = camera.get_single().ok()?;
+    camera.viewport_to_world(camera_transform, cursor).ok()
+}
+
+/// Rebuilds the input maps after the bindings change, including the one of a critter that is
+/// currently possessed.
+fn update_input_maps(
+    key_bindings: Res<KeyBindings>,
+    gamepad_bindings: Res<GamepadBindings>,
+    mut player_input_map: ResMut<PlayerInputMap>,
+    mut input_maps: Query<&mut InputMap<PlayerAction>>,
+) {
+    player_input_map.0 = input_map(&key_bindings, &gamepad_bindings);
+    for mut input_map in &mut input_maps {
+        *input_map = player_input_map.0.clone();
+    }
+}
+
 fn apply_controls(
-    actions: Query<&ActionState<PlayerAction>>,
-    mut query: Query<&mut TnuaController, With<Player>>,
+    mut query: Query<
+        (
+            Option<&mut TnuaController>,
+            Option<&mut KinematicMovement>,
+            &ActionState<PlayerAction>,
+            &Speed,
+            &GlobalTransform,
+            Option<&Habitat>,
+        ),
+        With<Player>,
+    >,
+    camera: Query<&GlobalTransform, With<Camera3d>>,
+    biome_map: Res<BiomeMap>,
 ) {
-    let Ok(mut controller) = query.get_single_mut() else {
+    let Ok((controller, movement, state, speed, transform, habitat)) = query.get_single_mut()
+    else {
         return;
     };
+    // The terrain slows a possessed critter down unless it is adapted to it.
+    let biome = biome_map.biome_at(transform.translation().xz());
+    let terrain_speed = habitat.map_or(biome.walk_speed(), |habitat| habitat.walk_speed(biome));
+
+    // Move relative to the camera's yaw, ignoring its pitch.
+    let (forward, right) = camera
+        .get_single()
+        .map_or((Vec3::NEG_Z, Vec3::X), |camera| {
+            let forward = camera.forward().with_y(0.0).try_normalize();
+            let forward = forward.unwrap_or(Vec3::NEG_Z);
+            (forward, forward.cross(Vec3::Y))
+        });
 
     let mut direction = Vec3::ZERO;
     let mut jumping = false;
 
-    for state in &actions {
-        if state.pressed(&PlayerAction::Up) {
-            direction -= Vec3::Z;
-        }
-        if state.pressed(&PlayerAction::Down) {
-            direction += Vec3::Z;
-        }
-        if state.pressed(&PlayerAction::Left) {
-            direction -= Vec3::X;
-        }
-        if state.pressed(&PlayerAction::Right) {
-            direction += Vec3::X;
-        }
-        if state.pressed(&PlayerAction::Jump) {
-            jumping = true;
+    if state.pressed(&PlayerAction::Up) {
+        direction += forward;
+    }
+    if state.pressed(&PlayerAction::Down) {
+        direction -= forward;
+    }
+    if state.pressed(&PlayerAction::Left) {
+        direction -= right;
+    }
+    if state.pressed(&PlayerAction::Right) {
+        direction += right;
+    }
+    if state.pressed(&PlayerAction::Jump) {
+        jumping = true;
+    }
+
+    // A possessed critter is bound by its own `Speed` gene and the biome it walks through, just like
+    // the AI-driven ones.
+    let desired_velocity = direction.normalize_or_zero() * 5.0 * speed.0 * terrain_speed;
+    // Lightweight critters have no body for Tnua to move, so they just glide.
+    if let Some(mut movement) = movement {
+        movement.velocity = desired_velocity;
+        if jumping {
+            movement.jump(JUMP_HEIGHT);
         }
+        return;
     }
+    let Some(mut controller) = controller else {
+        return;
+    };
 
     // Feed the basis every frame. Even if the player doesn't move - just use `desired_velocity:
     // Vec3::ZERO`. `TnuaController` starts without a basis, which will make the character collider
     // just fall.
     controller.basis(TnuaBuiltinWalk {
         // The `desired_velocity` determines how the character will move.
-        desired_velocity: direction.normalize_or_zero() * 10.0,
+        desired_velocity,
         // The `float_height` must be greater (even if by little) from the distance between the
         // character's center and the lowest point of its collider.
-        float_height: 1.3,
+        float_height: 1.5,
         // `TnuaBuiltinWalk` has many other fields for customizing the movement - but they have
         // sensible defaults. Refer to the `TnuaBuiltinWalk`'s documentation to learn what they do.
         ..Default::default()
@@ -142,7 +463,7 @@ fn apply_controls(
     if jumping {
         controller.action(TnuaBuiltinJump {
             // The height is the only mandatory field of the jump button.
-            height: 4.0,
+            height: JUMP_HEIGHT,
             // `TnuaBuiltinJump` also has customization fields with sensible defaults.
             ..Default::default()
         });
@@ -0,0 +1,184 @@
+//! The gameplay soundtrack, which follows the state of the ecosystem.
+//!
+//! Two looping layers play in sync for the whole session: a calm one and a tense one. Every
+//! [`CENSUS_INTERVAL`] the critters and food pellets are counted to pick a [`Mood`], and the layers
+//! are crossfaded to match it. A sting plays whenever a species dies out.
+
+use std::time::Duration;
+
+use bevy::{prelude::*, time::common_conditions::on_timer};
+
+use crate::{
+    asset_tracking::LoadResource,
+    audio::{Fade, Music},
+    game::{
+        critters::{FoodPellet, Herbivore, Preditor},
+        GAMEPLAY_RESOURCES,
+    },
+    screens::Screen,
+    AppSet,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Mood>();
+    app.register_type::<MusicLayer>();
+    app.load_resource_in_group::<GameplayMusic>(GAMEPLAY_RESOURCES);
+    app.init_resource::<Census>();
+    app.add_systems(OnEnter(Screen::Gameplay), start_music);
+    app.add_systems(OnExit(Screen::Gameplay), stop_music);
+    app.add_systems(
+        Update,
+        (take_census, play_extinction_sting, crossfade_layers)
+            .chain()
+            .run_if(in_state(Screen::Gameplay).and(on_timer(CENSUS_INTERVAL)))
+            .in_set(AppSet::Update),
+    );
+}
+
+/// How often the ecosystem is checked for a change of mood.
+const CENSUS_INTERVAL: Duration = Duration::from_secs(1);
+/// How long the layers take to fade in when gameplay starts and out when it ends.
+const START_STOP_FADE: Duration = Duration::from_secs(2);
+/// How long the layers take to crossfade when the mood changes.
+const CROSSFADE: Duration = Duration::from_secs(4);
+/// The music turns tense once there are more preditors than this per herbivore...
+const TENSE_PREDITORS_PER_HERBIVORE: f32 = 0.25;
+/// ...unless there are at least this many food pellets per herbivore, which keeps it calm.
+const ABUNDANT_FOOD_PER_HERBIVORE: f32 = 2.0;
+/// The extinction sting is a pitched down sound effect until it gets its own sample. See
+/// [`GameplayMusic::PATH_STING`].
+const STING_SPEED: f32 = 0.4;
+
+#[derive(Resource, Asset, Reflect, Clone)]
+pub struct GameplayMusic {
+    #[dependency]
+    calm: Handle<AudioSource>,
+    #[dependency]
+    tense: Handle<AudioSource>,
+    #[dependency]
+    sting: Handle<AudioSource>,
+}
+
+impl GameplayMusic {
+    pub const PATH_CALM: &'static str = "audio/music/Fluffing A Duck.ogg";
+    pub const PATH_TENSE: &'static str = "audio/music/Monkeys Spinning Monkeys.ogg";
+    /// A placeholder: the button press sound, played slowed down by [`STING_SPEED`]. Replace it
+    /// with a real sting sample once there is one.
+    pub const PATH_STING: &'static str = "audio/sound_effects/button_press.ogg";
+}
+
+impl FromWorld for GameplayMusic {
+    fn from_world(world: &mut World) -> Self {
+        let assets = world.resource::<AssetServer>();
+        Self {
+            calm: assets.load(Self::PATH_CALM),
+            tense: assets.load(Self::PATH_TENSE),
+            sting: assets.load(Self::PATH_STING),
+        }
+    }
+}
+
+/// The layer of the soundtrack that fits the state of the ecosystem.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+enum Mood {
+    Calm,
+    Tense,
+}
+
+/// Marks a looping layer of the gameplay soundtrack. It is audible while the ecosystem is in its
+/// [`Mood`].
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+struct MusicLayer(Mood);
+
+/// The population counted by the latest census.
+#[derive(Resource, Debug, Default)]
+struct Census {
+    herbivores: usize,
+    preditors: usize,
+    food_pellets: usize,
+    /// Species that died out since the previous census.
+    extinctions: usize,
+}
+
+impl Census {
+    fn mood(&self) -> Mood {
+        let herbivores = self.herbivores as f32;
+        let threatened = self.preditors as f32 > herbivores * TENSE_PREDITORS_PER_HERBIVORE;
+        let well_fed = self.food_pellets as f32 >= herbivores * ABUNDANT_FOOD_PER_HERBIVORE;
+        if threatened && !well_fed {
+            Mood::Tense
+        } else {
+            Mood::Calm
+        }
+    }
+}
+
+fn start_music(mut commands: Commands, music: Res<GameplayMusic>) {
+    commands.insert_resource(Census::default());
+    for (mood, handle) in [(Mood::Calm, &music.calm), (Mood::Tense, &music.tense)] {
+        commands.spawn((
+            Name::new(format!("{mood:?} music layer")),
+            AudioPlayer(handle.clone()),
+            PlaybackSettings::LOOP,
+            Music,
+            MusicLayer(mood),
+            // Both layers keep playing so they stay in sync, the one that doesn't fit is silent.
+            if mood == Mood::Calm {
+                Fade::fade_in(START_STOP_FADE)
+            } else {
+                Fade::silent()
+            },
+        ));
+    }
+}
+
+fn stop_music(mut layers: Query<&mut Fade, With<MusicLayer>>) {
+    for mut fade in &mut layers {
+        fade.out_and_despawn(START_STOP_FADE);
+    }
+}
+
+fn take_census(
+    mut census: ResMut<Census>,
+    herbivores: Query<(), With<Herbivore>>,
+    preditors: Query<(), With<Preditor>>,
+    food_pellets: Query<(), With<FoodPellet>>,
+) {
+    let herbivores = herbivores.iter().count();
+    let preditors = preditors.iter().count();
+    let died_out = |before: usize, after: usize| usize::from(before > 0 && after == 0);
+    *census = Census {
+        extinctions: died_out(census.herbivores, herbivores)
+            + died_out(census.preditors, preditors),
+        herbivores,
+        preditors,
+        food_pellets: food_pellets.iter().count(),
+    };
+}
+
+fn play_extinction_sting(mut commands: Commands, census: Res<Census>, music: Res<GameplayMusic>) {
+    if census.extinctions == 0 {
+        return;
+    }
+    commands.spawn((
+        Name::new("Extinction sting"),
+        AudioPlayer(music.sting.clone()),
+        PlaybackSettings::DESPAWN.with_speed(STING_SPEED),
+        Music,
+    ));
+}
+
+fn crossfade_layers(census: Res<Census>, mut layers: Query<(&MusicLayer, &mut Fade)>) {
+    let mood = census.mood();
+    for (layer, mut fade) in &mut layers {
+        // Layers that are already fading out for good are left alone.
+        if fade.despawn_when_silent {
+            continue;
+        }
+        let target = if layer.0 == mood { 1.0 } else { 0.0 };
+        if fade.target != target {
+            fade.to(target, CROSSFADE);
+        }
+    }
+}
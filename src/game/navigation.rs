@@ -0,0 +1,427 @@
+//! Pathfinding around obstacles.
+//!
+//! Once a level has loaded, its static colliders are baked into a [`NavGrid`] of walkable cells,
+//! a slice of cells per frame.
+//! Critters steer with their [`Navigation`]: straight at their goal when nothing is in the way,
+//! and along a path found with A* when something is. New paths are queued up and planned a few at a
+//! time, so that hundreds of critters changing their minds at once don't cause a hitch.
+
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, VecDeque},
+};
+
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use blenvy::{BlueprintInfo, BlueprintInstanceReady, GameWorldTag};
+
+use crate::{screens::Screen, AppSet};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Navigation>();
+    app.init_resource::<PathRequests>();
+    app.add_systems(OnEnter(Screen::Gameplay), clear_path_requests);
+    app.add_systems(
+        Update,
+        (
+            bake_nav_grid.run_if(nav_grid_baked(false)),
+            plan_paths.run_if(nav_grid_baked(true)),
+        )
+            .chain()
+            .in_set(AppSet::Update)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// Width of a cell in the [`NavGrid`].
+const NAV_CELL_SIZE: f32 = 4.0;
+/// How long to wait after a level has loaded before baking it, so that its colliders have made it
+/// into the physics world.
+const BAKE_DELAY: f32 = 0.5;
+/// How many cells are baked per frame, so that baking a large level doesn't freeze the game.
+const BAKE_CELLS_PER_FRAME: usize = 1024;
+/// Rays looking for the ground start this high up.
+const BAKE_HEIGHT: f32 = 100.0;
+/// Obstacles lower than this above the ground can be walked over.
+const STEP_HEIGHT: f32 = 0.6;
+/// Obstacles higher than this above the ground can be walked under.
+const CLEARANCE: f32 = 2.6;
+/// How many surfaces a ray looking for the ground can pass through, including the critters and
+/// food pellets in the way.
+const MAX_GROUND_HITS: u32 = 16;
+/// How many paths are planned per frame at most.
+const PATHS_PER_FRAME: usize = 16;
+/// How many cells a single path search may visit before giving up.
+const MAX_SEARCH_CELLS: usize = 4000;
+/// How far a critter's goal has to move before its path is planned again.
+const REPLAN_DISTANCE: f32 = 5.0;
+/// How close a critter has to get to a waypoint to head for the next one. Critters only steer
+/// every so often, so this is about a cell wide.
+const WAYPOINT_RADIUS: f32 = 4.0;
+
+/// Which parts of the level critters can walk through, on a square grid centered on the origin.
+/// Points outside the grid are not walkable.
+#[derive(Resource, Debug)]
+pub struct NavGrid {
+    size: f32,
+    cells_per_side: usize,
+    walkable: Vec<bool>,
+    /// The next cell to bake. The grid is baked once this is past the last cell.
+    next_bake_cell: usize,
+}
+
+impl NavGrid {
+    /// A `size` by `size` grid that is baked from the level's colliders once it has loaded.
+    pub fn new(size: f32) -> Self {
+        let cells_per_side = (size / NAV_CELL_SIZE).ceil() as usize;
+        Self {
+            size,
+            cells_per_side,
+            walkable: vec![true; cells_per_side * cells_per_side],
+            next_bake_cell: 0,
+        }
+    }
+
+    pub fn is_baked(&self) -> bool {
+        self.next_bake_cell >= self.walkable.len()
+    }
+
+    pub fn is_walkable(&self, point: Vec2) -> bool {
+        self.cell(point).is_some_and(|cell| self.walkable[cell])
+    }
+
+    /// Whether a critter can walk in a straight line from `from` to `to`.
+    pub fn is_clear(&self, from: Vec2, to: Vec2) -> bool {
+        let steps = (from.distance(to) / (NAV_CELL_SIZE / 2.0)).ceil().max(1.0) as usize;
+        (1..=steps).all(|step| self.is_walkable(from.lerp(to, step as f32 / steps as f32)))
+    }
+
+    /// The waypoints of a path from `from` to `to`, in order and ending at `to`, if there is one.
+    pub fn find_path(&self, from: Vec2, to: Vec2) -> Option<Vec<Vec2>> {
+        let start = self.cell(from)?;
+        let goal = self.cell(to)?;
+        // Cells are 10 apart straight and 14 diagonally.
+        let estimate = |cell: usize| {
+            let (dx, dz) = (
+                (cell % self.cells_per_side).abs_diff(goal % self.cells_per_side) as u32,
+                (cell / self.cells_per_side).abs_diff(goal / self.cells_per_side) as u32,
+            );
+            10 * dx.max(dz) + 4 * dx.min(dz)
+        };
+
+        let mut cost = vec![u32::MAX; self.walkable.len()];
+        let mut came_from = vec![usize::MAX; self.walkable.len()];
+        let mut open = BinaryHeap::from([Reverse((estimate(start), start))]);
+        cost[start] = 0;
+        let mut searched = 0;
+        while let Some(Reverse((priority, cell))) = open.pop() {
+            // A cheaper way to this cell was found after this entry was queued, and it has already
+            // been searched from.
+            if priority > cost[cell] + estimate(cell) {
+                continue;
+            }
+            if cell == goal {
+                return Some(self.waypoints(from, to, &came_from, goal));
+            }
+            searched += 1;
+            if searched > MAX_SEARCH_CELLS {
+                return None;
+            }
+            for (neighbour, step_cost) in self.neighbours(cell) {
+                // The goal itself may be blocked, like food that landed on a rock.
+                if !self.walkable[neighbour] && neighbour != goal {
+                    continue;
+                }
+                let neighbour_cost = cost[cell] + step_cost;
+                if neighbour_cost < cost[neighbour] {
+                    cost[neighbour] = neighbour_cost;
+                    came_from[neighbour] = cell;
+                    open.push(Reverse((neighbour_cost + estimate(neighbour), neighbour)));
+                }
+            }
+        }
+        None
+    }
+
+    /// Walks a search back from `goal` and drops the waypoints that can be skipped by walking in a
+    /// straight line.
+    fn waypoints(&self, from: Vec2, to: Vec2, came_from: &[usize], goal: usize) -> Vec<Vec2> {
+        let mut cells = vec![to];
+        let mut cell = came_from[goal];
+        while cell != usize::MAX {
+            cells.push(self.cell_center(cell));
+            cell = came_from[cell];
+        }
+        cells.reverse();
+
+        let mut waypoints = Vec::new();
+        let mut position = from;
+        let mut next = 0;
+        while next < cells.len() {
+            let furthest = (next..cells.len())
+                .rev()
+                .find(|&i| self.is_clear(position, cells[i]))
+                .unwrap_or(next);
+            position = cells[furthest];
+            waypoints.push(position);
+            next = furthest + 1;
+        }
+        waypoints
+    }
+
+    fn neighbours(&self, cell: usize) -> impl Iterator<Item = (usize, u32)> + '_ {
+        let (x, z) = (cell % self.cells_per_side, cell / self.cells_per_side);
+        let last = self.cells_per_side - 1;
+        [
+            (-1, 0),
+            (1, 0),
+            (0, -1),
+            (0, 1),
+            (-1, -1),
+            (1, -1),
+            (-1, 1),
+            (1, 1),
+        ]
+        .into_iter()
+        .filter_map(move |(dx, dz): (isize, isize)| {
+            let neighbour_x = x.checked_add_signed(dx).filter(|&x| x <= last)?;
+            let neighbour_z = z.checked_add_signed(dz).filter(|&z| z <= last)?;
+            let neighbour = neighbour_z * self.cells_per_side + neighbour_x;
+            if dx == 0 || dz == 0 {
+                return Some((neighbour, 10));
+            }
+            // No cutting corners past obstacles.
+            let side_x = z * self.cells_per_side + neighbour_x;
+            let side_z = neighbour_z * self.cells_per_side + x;
+            (self.walkable[side_x] && self.walkable[side_z]).then_some((neighbour, 14))
+        })
+    }
+
+    fn cell(&self, point: Vec2) -> Option<usize> {
+        let cell = ((point + self.size / 2.0) / NAV_CELL_SIZE).floor();
+        let in_bounds = |value: f32| value >= 0.0 && (value as usize) < self.cells_per_side;
+        (in_bounds(cell.x) && in_bounds(cell.y))
+            .then_some(cell.y as usize * self.cells_per_side + cell.x as usize)
+    }
+
+    fn cell_center(&self, cell: usize) -> Vec2 {
+        let (x, z) = (cell % self.cells_per_side, cell / self.cells_per_side);
+        (Vec2::new(x as f32, z as f32) + 0.5) * NAV_CELL_SIZE - self.size / 2.0
+    }
+}
+
+/// A run condition for whether the level's [`NavGrid`] exists and has been baked or not.
+fn nav_grid_baked(baked: bool) -> impl Fn(Option<Res<NavGrid>>) -> bool {
+    move |grid| grid.is_some_and(|grid| grid.is_baked() == baked)
+}
+
+/// Where a critter is headed and how it gets there.
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component)]
+pub struct Navigation {
+    goal: Option<Vec2>,
+    /// The rest of the path to the goal, in reverse order. Empty while heading straight for it.
+    waypoints: Vec<Vec2>,
+    planning: bool,
+}
+
+impl Navigation {
+    /// The direction to walk in from `position` to get to `goal`, on the XZ plane. A path is
+    /// requested whenever the goal moves too far or the way to it is blocked.
+    pub fn steer(
+        &mut self,
+        entity: Entity,
+        position: Vec3,
+        goal: Vec3,
+        requests: &mut PathRequests,
+    ) -> Vec3 {
+        let (position, goal) = (position.xz(), goal.xz());
+        if self
+            .goal
+            .is_none_or(|old_goal| old_goal.distance(goal) > REPLAN_DISTANCE)
+        {
+            self.goal = Some(goal);
+            self.waypoints.clear();
+            if !self.planning {
+                self.planning = true;
+                requests.0.push_back(entity);
+            }
+        }
+        while self
+            .waypoints
+            .last()
+            .is_some_and(|waypoint| waypoint.distance(position) < WAYPOINT_RADIUS)
+        {
+            self.waypoints.pop();
+        }
+        let target = self.waypoints.last().copied().unwrap_or(goal);
+        let direction = target - position;
+        Vec3::new(direction.x, 0.0, direction.y)
+    }
+
+    /// Forgets the goal, for when the critter wanders off.
+    pub fn stop(&mut self) {
+        self.goal = None;
+        self.waypoints.clear();
+    }
+}
+
+/// Critters waiting for a path to be planned, first come first served.
+#[derive(Resource, Debug, Default)]
+pub struct PathRequests(VecDeque<Entity>);
+
+fn clear_path_requests(mut requests: ResMut<PathRequests>) {
+    requests.0.clear();
+}
+
+/// Marks the cells of the [`NavGrid`] that have no ground or an obstacle in them as not walkable,
+/// [`BAKE_CELLS_PER_FRAME`] at a time.
+fn bake_nav_grid(
+    mut grid: ResMut<NavGrid>,
+    time: Res<Time>,
+    // How long the level has been ready for.
+    mut ready_for: Local<f32>,
+    levels: Query<Has<BlueprintInstanceReady>, (With<GameWorldTag>, With<BlueprintInfo>)>,
+    spatial_query: SpatialQuery,
+    colliders: Query<&ColliderParent>,
+    bodies: Query<&RigidBody>,
+) {
+    if grid.next_bake_cell == 0 {
+        if !levels.iter().all(|ready| ready) {
+            *ready_for = 0.0;
+            return;
+        }
+        *ready_for += time.delta_secs();
+        if *ready_for < BAKE_DELAY {
+            return;
+        }
+        *ready_for = 0.0;
+    }
+
+    // Critters and food pellets move around, so they aren't obstacles.
+    let is_static = |entity: Entity| {
+        colliders
+            .get(entity)
+            .ok()
+            .and_then(|parent| bodies.get(parent.get()).ok())
+            .is_none_or(RigidBody::is_static)
+    };
+    let filter = SpatialQueryFilter::default();
+    let obstacle_shape = Collider::cuboid(
+        NAV_CELL_SIZE * 0.75,
+        CLEARANCE - STEP_HEIGHT,
+        NAV_CELL_SIZE * 0.75,
+    );
+    let cells =
+        grid.next_bake_cell..(grid.next_bake_cell + BAKE_CELLS_PER_FRAME).min(grid.walkable.len());
+    grid.next_bake_cell = cells.end;
+    for cell in cells {
+        let center = grid.cell_center(cell);
+        // The ground is the lowest surface, under any obstacles on top of it.
+        let ground = spatial_query
+            .ray_hits(
+                Vec3::new(center.x, BAKE_HEIGHT, center.y),
+                Dir3::NEG_Y,
+                BAKE_HEIGHT * 2.0,
+                MAX_GROUND_HITS,
+                true,
+                &filter,
+            )
+            .into_iter()
+            .filter(|hit| is_static(hit.entity))
+            .map(|hit| hit.distance)
+            .max_by(f32::total_cmp);
+        grid.walkable[cell] = ground.is_some_and(|distance| {
+            let ground_height = BAKE_HEIGHT - distance;
+            let obstacle_center = Vec3::new(
+                center.x,
+                ground_height + (STEP_HEIGHT + CLEARANCE) / 2.0,
+                center.y,
+            );
+            !spatial_query
+                .shape_intersections(&obstacle_shape, obstacle_center, Quat::IDENTITY, &filter)
+                .into_iter()
+                .any(is_static)
+        });
+    }
+}
+
+/// Plans the paths of the critters that asked for one, up to [`PATHS_PER_FRAME`] at a time.
+fn plan_paths(
+    grid: Res<NavGrid>,
+    mut requests: ResMut<PathRequests>,
+    mut critters: Query<(&GlobalTransform, &mut Navigation)>,
+) {
+    let mut planned = 0;
+    while planned < PATHS_PER_FRAME {
+        let Some(entity) = requests.0.pop_front() else {
+            break;
+        };
+        let Ok((transform, mut navigation)) = critters.get_mut(entity) else {
+            continue;
+        };
+        navigation.planning = false;
+        let Some(goal) = navigation.goal else {
+            continue;
+        };
+        let position = transform.translation().xz();
+        // Nothing to plan when the way is clear.
+        if grid.is_clear(position, goal) {
+            continue;
+        }
+        planned += 1;
+        if let Some(mut waypoints) = grid.find_path(position, goal) {
+            waypoints.reverse();
+            navigation.waypoints = waypoints;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A grid 10 cells wide, with a wall of blocked cells at x = 5 for each of `wall_rows`.
+    fn grid_with_wall(wall_rows: impl IntoIterator<Item = usize>) -> NavGrid {
+        let mut grid = NavGrid::new(10.0 * NAV_CELL_SIZE);
+        for z in wall_rows {
+            grid.walkable[z * grid.cells_per_side + 5] = false;
+        }
+        grid
+    }
+
+    /// The center of the cell at `x`, `z`.
+    fn point(grid: &NavGrid, x: usize, z: usize) -> Vec2 {
+        grid.cell_center(z * grid.cells_per_side + x)
+    }
+
+    #[test]
+    fn straight_path_goes_right_to_the_goal() {
+        let grid = grid_with_wall([]);
+        let (from, to) = (point(&grid, 1, 4), point(&grid, 8, 4));
+        assert_eq!(grid.find_path(from, to), Some(vec![to]));
+    }
+
+    #[test]
+    fn path_detours_around_blocked_cells() {
+        let grid = grid_with_wall(0..8);
+        let (from, to) = (point(&grid, 1, 4), point(&grid, 8, 4));
+        let waypoints = grid
+            .find_path(from, to)
+            .expect("there is a way around the wall");
+        assert!(waypoints.len() > 1);
+        assert_eq!(waypoints.last(), Some(&to));
+        let mut position = from;
+        for waypoint in waypoints {
+            assert!(grid.is_clear(position, waypoint));
+            position = waypoint;
+        }
+    }
+
+    #[test]
+    fn no_path_to_a_walled_off_goal() {
+        let grid = grid_with_wall(0..10);
+        let (from, to) = (point(&grid, 1, 4), point(&grid, 8, 4));
+        assert_eq!(grid.find_path(from, to), None);
+    }
+}
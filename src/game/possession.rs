@@ -0,0 +1,126 @@
+//! Taking control of a critter and handing it back to the AI.
+//!
+//! With the sandbox's possess tool selected, click on any herbivore or preditor to possess it and
+//! press R to release it. The possessed critter is driven by [`apply_controls`](super::apply_controls)
+//! but keeps its genes, energy and diet, so it still has to eat to survive and will reproduce like
+//! any other critter.
+
+use avian3d::prelude::*;
+use bevy::{input::common_conditions::input_just_pressed, prelude::*, window::PrimaryWindow};
+use leafwing_input_manager::prelude::*;
+use smooth_bevy_cameras::LookTransform;
+
+use crate::{
+    game::{
+        critters::Critter,
+        cursor_ray,
+        sandbox::{pointer_over_ui, SandboxTool},
+        GameMode, Player, PlayerAction, PlayerInputMap,
+    },
+    screens::{PauseMenu, Screen},
+    AppSet,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        (
+            possess_critter.run_if(
+                input_just_pressed(POSSESS_BUTTON)
+                    .and(resource_equals(SandboxTool::Possess))
+                    .and(not(pointer_over_ui)),
+            ),
+            release_critter.run_if(input_just_pressed(RELEASE_KEY)),
+        )
+            .in_set(AppSet::RecordInput)
+            .run_if(in_state(PauseMenu::Closed).and(resource_equals(GameMode::Sandbox))),
+    );
+    app.add_systems(
+        Update,
+        follow_player
+            .in_set(AppSet::Update)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+const POSSESS_BUTTON: MouseButton = MouseButton::Left;
+const RELEASE_KEY: KeyCode = KeyCode::KeyR;
+
+/// How far away from the camera a critter can be picked.
+const MAX_PICK_DISTANCE: f32 = 1000.0;
+
+fn possess_critter(
+    mut commands: Commands,
+    window: Query<&Window, With<PrimaryWindow>>,
+    camera: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    spatial_query: SpatialQuery,
+    parents: Query<&Parent>,
+    critters: Query<(), With<Critter>>,
+    players: Query<Entity, With<Player>>,
+    input_map: Res<PlayerInputMap>,
+    keys: Res<ButtonInput<KeyCode>>,
+) {
+    // CTRL + left mouse drag orbits the camera.
+    if keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        return;
+    }
+    let Some(ray) = cursor_ray(&window, &camera) else {
+        return;
+    };
+    let Some(hit) = spatial_query.cast_ray(
+        ray.origin,
+        ray.direction,
+        MAX_PICK_DISTANCE,
+        true,
+        &SpatialQueryFilter::default(),
+    ) else {
+        return;
+    };
+    // Colliders may live on a child of the blueprint, so look up the hierarchy for the critter.
+    let Some(critter) = std::iter::once(hit.entity)
+        .chain(parents.iter_ancestors(hit.entity))
+        .find(|&entity| critters.contains(entity))
+    else {
+        return;
+    };
+
+    for player in &players {
+        release(&mut commands, player);
+    }
+    possess(&mut commands, critter, &input_map);
+}
+
+fn release_critter(mut commands: Commands, players: Query<Entity, With<Player>>) {
+    for player in &players {
+        release(&mut commands, player);
+    }
+}
+
+/// Puts a critter under player control.
+pub(super) fn possess(commands: &mut Commands, entity: Entity, input_map: &PlayerInputMap) {
+    commands
+        .entity(entity)
+        .insert((Player, InputManagerBundle::with_map(input_map.0.clone())));
+}
+
+/// Hands a possessed critter back to the AI movement systems.
+fn release(commands: &mut Commands, entity: Entity) {
+    commands
+        .entity(entity)
+        .remove::<(Player, InputManagerBundle<PlayerAction>)>();
+}
+
+/// Keeps the orbit camera centered on the possessed critter without changing its angle or zoom.
+fn follow_player(
+    player: Query<&GlobalTransform, With<Player>>,
+    mut cameras: Query<&mut LookTransform>,
+) {
+    let Ok(player) = player.get_single() else {
+        return;
+    };
+    for mut look in &mut cameras {
+        let offset = player.translation() - look.target;
+        look.target += offset;
+        look.eye += offset;
+    }
+}
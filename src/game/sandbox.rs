@@ -0,0 +1,404 @@
+//! God-mode tools for setting up scenarios in the middle of a sandbox run.
+//!
+//! A toolbar at the bottom of the screen selects what a left click does: possess a critter, spawn
+//! herbivores, preditors or food pellets at the cursor, or erase everything within a radius.
+//! With the brush enabled, holding the mouse button keeps applying the tool.
+
+use avian3d::prelude::*;
+use bevy::{prelude::*, ui::Val::*, window::PrimaryWindow};
+use rand::prelude::*;
+
+use crate::{
+    game::{
+        critters::{
+            CritterDied, DeathCause, FoodPellet, Herbivore, Preditor, ReproductionEnergy, Speed,
+        },
+        cursor_ray, GameMode,
+    },
+    screens::{PauseMenu, Screen},
+    theme::prelude::*,
+    AppSet,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<SandboxTool>();
+    app.register_type::<SandboxBrush>();
+    app.register_type::<SpawnGenes>();
+    app.init_resource::<SandboxTool>();
+    app.init_resource::<SandboxBrush>();
+    app.init_resource::<SpawnGenes>();
+
+    app.add_systems(
+        OnEnter(Screen::Gameplay),
+        spawn_toolbar.run_if(resource_equals(GameMode::Sandbox)),
+    );
+    app.add_systems(
+        Update,
+        (
+            apply_tool.in_set(AppSet::RecordInput).run_if(
+                in_state(PauseMenu::Closed)
+                    .and(not(resource_equals(SandboxTool::Possess)))
+                    .and(not(pointer_over_ui)),
+            ),
+            (
+                update_tool_buttons.run_if(
+                    resource_changed::<SandboxTool>
+                        .or(resource_changed::<SandboxBrush>)
+                        .or(resource_changed::<SpawnGenes>),
+                ),
+                update_gene_labels.run_if(resource_changed::<SpawnGenes>),
+            )
+                .in_set(AppSet::Update),
+        )
+            .run_if(in_state(Screen::Gameplay).and(resource_equals(GameMode::Sandbox))),
+    );
+}
+
+/// What a left click does in the sandbox.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+#[reflect(Resource)]
+pub enum SandboxTool {
+    /// Take control of the clicked critter.
+    #[default]
+    Possess,
+    SpawnHerbivore,
+    SpawnPreditor,
+    SpawnFoodPellet,
+    /// Remove all critters and food pellets within [`SandboxBrush::radius`].
+    Erase,
+}
+
+#[derive(Resource, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct SandboxBrush {
+    /// Keep applying the tool while the mouse button is held.
+    pub enabled: bool,
+    /// Radius around the cursor that brush spawns are scattered in and erasing affects.
+    pub radius: f32,
+}
+
+impl Default for SandboxBrush {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            radius: 10.0,
+        }
+    }
+}
+
+/// Genes given to critters spawned with the sandbox tools.
+#[derive(Resource, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct SpawnGenes {
+    /// Let the spawn hooks roll random genes instead of using the values below.
+    pub random: bool,
+    pub speed: f32,
+    pub reproduction_energy: f32,
+}
+
+impl Default for SpawnGenes {
+    fn default() -> Self {
+        Self {
+            random: true,
+            speed: 1.25,
+            reproduction_energy: 10.0,
+        }
+    }
+}
+
+/// Seconds between two applications of the brush.
+const BRUSH_INTERVAL: f32 = 0.05;
+/// Spawned entities are dropped from this height above the clicked point.
+const SPAWN_HEIGHT: f32 = 2.0;
+/// How far away from the camera the tools can be applied.
+const MAX_TOOL_DISTANCE: f32 = 1000.0;
+
+const SPEED_STEP: f32 = 0.25;
+const REPRODUCTION_ENERGY_STEP: f32 = 1.0;
+
+/// Whether the cursor is over a UI element, in which case clicks are meant for the UI.
+pub(super) fn pointer_over_ui(interactions: Query<&Interaction>) -> bool {
+    interactions
+        .iter()
+        .any(|interaction| *interaction != Interaction::None)
+}
+
+fn apply_tool(
+    mut commands: Commands,
+    tool: Res<SandboxTool>,
+    brush: Res<SandboxBrush>,
+    genes: Res<SpawnGenes>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    time: Res<Time>,
+    // Time left until the brush is applied again.
+    mut cooldown: Local<f32>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    camera: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    spatial_query: SpatialQuery,
+    erasable: Query<
+        (Entity, &GlobalTransform, Has<FoodPellet>),
+        Or<(With<Herbivore>, With<Preditor>, With<FoodPellet>)>,
+    >,
+    mut died: EventWriter<CritterDied>,
+) {
+    if mouse.just_pressed(MouseButton::Left) {
+        *cooldown = 0.0;
+    } else if brush.enabled && mouse.pressed(MouseButton::Left) {
+        *cooldown -= time.delta_secs();
+        if *cooldown > 0.0 {
+            return;
+        }
+    } else {
+        return;
+    }
+    *cooldown = BRUSH_INTERVAL;
+
+    let Some(ray) = cursor_ray(&window, &camera) else {
+        return;
+    };
+    let distance = spatial_query
+        .cast_ray(
+            ray.origin,
+            ray.direction,
+            MAX_TOOL_DISTANCE,
+            true,
+            &SpatialQueryFilter::default(),
+        )
+        .map(|hit| hit.distance)
+        .or_else(|| ray.intersect_plane(Vec3::ZERO, InfinitePlane3d::new(Vec3::Y)));
+    let Some(distance) = distance else {
+        return;
+    };
+    let point = ray.get_point(distance);
+
+    // A single click spawns exactly at the cursor, the brush scatters spawns around it.
+    let mut rng = thread_rng();
+    let location = if brush.enabled {
+        let offset = Vec2::from_angle(rng.gen_range(0.0..std::f32::consts::TAU))
+            * brush.radius
+            * rng.gen::<f32>().sqrt();
+        point + Vec3::new(offset.x, SPAWN_HEIGHT, offset.y)
+    } else {
+        point + Vec3::Y * SPAWN_HEIGHT
+    };
+    let transform = Transform::from_translation(location);
+
+    match *tool {
+        SandboxTool::Possess => {}
+        SandboxTool::SpawnHerbivore => {
+            let mut critter = commands.spawn((Herbivore, transform));
+            if !genes.random {
+                critter.insert((
+                    Speed(genes.speed),
+                    ReproductionEnergy(genes.reproduction_energy),
+                ));
+            }
+        }
+        SandboxTool::SpawnPreditor => {
+            let mut critter = commands.spawn((Preditor, transform));
+            if !genes.random {
+                critter.insert((
+                    Speed(genes.speed),
+                    ReproductionEnergy(genes.reproduction_energy),
+                ));
+            }
+        }
+        SandboxTool::SpawnFoodPellet => {
+            commands.spawn((FoodPellet, transform));
+        }
+        SandboxTool::Erase => {
+            let radius_squared = brush.radius * brush.radius;
+            for (entity, transform, is_food_pellet) in &erasable {
+                let translation = transform.translation();
+                if translation.distance_squared(point) > radius_squared {
+                    continue;
+                }
+                commands.entity(entity).despawn_recursive();
+                if !is_food_pellet {
+                    died.send(CritterDied {
+                        critter: entity,
+                        translation,
+                        cause: DeathCause::Removed,
+                    });
+                }
+            }
+        }
+    }
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct ToolButton(SandboxTool);
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct BrushButton;
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct RandomGenesButton;
+
+#[derive(Component, Reflect, Clone, Copy)]
+#[reflect(Component)]
+enum GeneLabel {
+    Speed,
+    ReproductionEnergy,
+}
+
+impl GeneLabel {
+    fn text(self, genes: &SpawnGenes) -> String {
+        match self {
+            GeneLabel::Speed => format!("Speed: {:.2}", genes.speed),
+            GeneLabel::ReproductionEnergy => {
+                format!("Reproduction energy: {:.0}", genes.reproduction_energy)
+            }
+        }
+    }
+
+    fn width(self) -> f32 {
+        match self {
+            GeneLabel::Speed => 120.0,
+            GeneLabel::ReproductionEnergy => 250.0,
+        }
+    }
+}
+
+fn spawn_toolbar(
+    mut commands: Commands,
+    selected: Res<SandboxTool>,
+    brush: Res<SandboxBrush>,
+    genes: Res<SpawnGenes>,
+) {
+    commands
+        .spawn((
+            Name::new("Sandbox toolbar"),
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Px(10.0),
+                width: Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Px(6.0),
+                ..default()
+            },
+            StateScoped(Screen::Gameplay),
+        ))
+        .with_children(|toolbar| {
+            toolbar.spawn(toolbar_row()).with_children(|row| {
+                for (text, tool) in [
+                    ("Possess", SandboxTool::Possess),
+                    ("Herbivore", SandboxTool::SpawnHerbivore),
+                    ("Preditor", SandboxTool::SpawnPreditor),
+                    ("Food", SandboxTool::SpawnFoodPellet),
+                    ("Erase", SandboxTool::Erase),
+                ] {
+                    row.small_button(text)
+                        .insert((ToolButton(tool), selection_outline(tool == *selected)))
+                        .observe(select_tool(tool));
+                }
+                row.small_button("Brush")
+                    .insert((BrushButton, selection_outline(brush.enabled)))
+                    .observe(toggle_brush);
+            });
+            toolbar.spawn(toolbar_row()).with_children(|row| {
+                row.small_button("Random genes")
+                    .insert((RandomGenesButton, selection_outline(genes.random)))
+                    .observe(toggle_random_genes);
+                for (label, step) in [
+                    (GeneLabel::Speed, Vec2::new(SPEED_STEP, 0.0)),
+                    (
+                        GeneLabel::ReproductionEnergy,
+                        Vec2::new(0.0, REPRODUCTION_ENERGY_STEP),
+                    ),
+                ] {
+                    row.small_button("-").observe(adjust_genes(-step));
+                    row.label(label.text(&genes)).insert((
+                        Node {
+                            width: Px(label.width()),
+                            ..default()
+                        },
+                        label,
+                    ));
+                    row.small_button("+").observe(adjust_genes(step));
+                }
+            });
+        });
+}
+
+fn toolbar_row() -> impl Bundle {
+    (
+        Name::new("Toolbar row"),
+        Node {
+            flex_direction: FlexDirection::Row,
+            align_items: AlignItems::Center,
+            column_gap: Px(6.0),
+            ..default()
+        },
+    )
+}
+
+fn selection_outline(selected: bool) -> Outline {
+    Outline::new(Px(2.0), Px(0.0), selection_color(selected))
+}
+
+fn selection_color(selected: bool) -> Color {
+    if selected {
+        ui_palette::SELECTED_OUTLINE
+    } else {
+        Color::NONE
+    }
+}
+
+fn select_tool(tool: SandboxTool) -> impl Fn(Trigger<OnPress>, ResMut<SandboxTool>) {
+    move |_trigger, mut selected| *selected = tool
+}
+
+fn toggle_brush(_trigger: Trigger<OnPress>, mut brush: ResMut<SandboxBrush>) {
+    brush.enabled = !brush.enabled;
+}
+
+fn toggle_random_genes(_trigger: Trigger<OnPress>, mut genes: ResMut<SpawnGenes>) {
+    genes.random = !genes.random;
+}
+
+/// Adjusts speed by `step.x` and reproduction energy by `step.y`.
+fn adjust_genes(step: Vec2) -> impl Fn(Trigger<OnPress>, ResMut<SpawnGenes>) {
+    move |_trigger, mut genes| {
+        genes.speed = (genes.speed + step.x).max(0.0);
+        genes.reproduction_energy = (genes.reproduction_energy + step.y).max(0.0);
+        // Tweaking a gene only makes sense if it is going to be used.
+        genes.random = false;
+    }
+}
+
+fn update_tool_buttons(
+    tool: Res<SandboxTool>,
+    brush: Res<SandboxBrush>,
+    genes: Res<SpawnGenes>,
+    mut tool_buttons: Query<(&ToolButton, &mut Outline)>,
+    mut brush_buttons: Query<&mut Outline, (With<BrushButton>, Without<ToolButton>)>,
+    mut gene_buttons: Query<
+        &mut Outline,
+        (
+            With<RandomGenesButton>,
+            Without<ToolButton>,
+            Without<BrushButton>,
+        ),
+    >,
+) {
+    for (button, mut outline) in &mut tool_buttons {
+        outline.color = selection_color(button.0 == *tool);
+    }
+    for mut outline in &mut brush_buttons {
+        outline.color = selection_color(brush.enabled);
+    }
+    for mut outline in &mut gene_buttons {
+        outline.color = selection_color(genes.random);
+    }
+}
+
+fn update_gene_labels(genes: Res<SpawnGenes>, mut labels: Query<(&GeneLabel, &mut Text)>) {
+    for (label, mut text) in &mut labels {
+        text.0 = label.text(&genes);
+    }
+}
@@ -0,0 +1,162 @@
+//! Saving a sandbox ecosystem to disk and picking it up again later.
+//!
+//! Only what the simulation needs to carry on is saved: every critter's position and genes and
+//! every food pellet's position. Possession and camera placement are not restored.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    game::{
+        biomes::{Biome, Habitat},
+        critters::{
+            Energy, FoodPellet, Herbivore, Nocturnality, Preditor, ReproductionEnergy, Speed,
+        },
+        level::{SelectedLevel, SimulationSeed},
+        water::{DroughtTolerance, Hydration},
+    },
+    persistence,
+};
+
+/// A snapshot of an ecosystem. Insert it as a resource before entering
+/// [`Screen::Gameplay`](crate::screens::Screen::Gameplay) to spawn the level from it instead of
+/// from scratch.
+#[derive(Resource, Debug, Default, Serialize, Deserialize)]
+pub struct SavedEcosystem {
+    pub seed: u64,
+    /// Saves from before generated levels were always in the handcrafted world.
+    #[serde(default)]
+    pub level: SelectedLevel,
+    pub critters: Vec<SavedCritter>,
+    pub food_pellets: Vec<Vec3>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SavedCritter {
+    pub diet: Diet,
+    pub translation: Vec3,
+    pub energy: u32,
+    pub speed: f32,
+    pub reproduction_energy: f32,
+    /// Saves from before the day/night cycle only had diurnal critters.
+    #[serde(default)]
+    pub nocturnality: f32,
+    /// Saves from before biomes have no habitat, so critters adapt to wherever they are loaded.
+    #[serde(default)]
+    pub habitat: Option<Biome>,
+    /// Saves from before water have critters start out with a full drink.
+    #[serde(default)]
+    pub hydration: Option<u32>,
+    /// Saves from before water have critters without any drought tolerance, which keeps their
+    /// metabolism as it was.
+    #[serde(default)]
+    pub drought_tolerance: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Diet {
+    Herbivore,
+    Preditor,
+}
+
+impl SavedEcosystem {
+    pub const FILE_NAME: &'static str = "ecosystem.ron";
+
+    pub fn exists() -> bool {
+        persistence::exists(Self::FILE_NAME)
+    }
+
+    pub fn load() -> Self {
+        persistence::load(Self::FILE_NAME)
+    }
+
+    /// Spawns the saved critters and food pellets. Their blueprints are added by the usual spawn
+    /// hooks, which keep the saved genes.
+    pub(super) fn spawn(self, world: &mut World) {
+        for critter in self.critters {
+            let mut entity = world.spawn((
+                Transform::from_translation(critter.translation),
+                Energy(critter.energy),
+                Speed(critter.speed),
+                ReproductionEnergy(critter.reproduction_energy),
+                Nocturnality(critter.nocturnality),
+                DroughtTolerance(critter.drought_tolerance),
+            ));
+            if let Some(biome) = critter.habitat {
+                entity.insert(Habitat(biome));
+            }
+            if let Some(hydration) = critter.hydration {
+                entity.insert(Hydration(hydration));
+            }
+            match critter.diet {
+                Diet::Herbivore => entity.insert(Herbivore),
+                Diet::Preditor => entity.insert(Preditor),
+            };
+        }
+        for translation in self.food_pellets {
+            world.spawn((FoodPellet, Transform::from_translation(translation)));
+        }
+    }
+}
+
+/// A [`Command`] that writes the current ecosystem to disk.
+pub fn save_ecosystem(world: &mut World) {
+    let seed = world.resource::<SimulationSeed>().0;
+    let level = world.resource::<SelectedLevel>().clone();
+    let mut critters = world.query_filtered::<(
+        &GlobalTransform,
+        &Energy,
+        &Speed,
+        &ReproductionEnergy,
+        &Nocturnality,
+        Option<&Habitat>,
+        Option<&Hydration>,
+        Option<&DroughtTolerance>,
+        Has<Preditor>,
+    ), Or<(With<Herbivore>, With<Preditor>)>>();
+    let critters = critters
+        .iter(world)
+        .map(
+            |(
+                transform,
+                energy,
+                speed,
+                reproduction_energy,
+                nocturnality,
+                habitat,
+                hydration,
+                drought_tolerance,
+                is_preditor,
+            )| {
+                SavedCritter {
+                    diet: if is_preditor {
+                        Diet::Preditor
+                    } else {
+                        Diet::Herbivore
+                    },
+                    translation: transform.translation(),
+                    energy: energy.0,
+                    speed: speed.0,
+                    reproduction_energy: reproduction_energy.0,
+                    nocturnality: nocturnality.0,
+                    habitat: habitat.map(|habitat| habitat.0),
+                    hydration: hydration.map(|hydration| hydration.0),
+                    drought_tolerance: drought_tolerance.map_or(0.0, |tolerance| tolerance.0),
+                }
+            },
+        )
+        .collect();
+    let food_pellets = world
+        .query_filtered::<&GlobalTransform, With<FoodPellet>>()
+        .iter(world)
+        .map(GlobalTransform::translation)
+        .collect();
+
+    let saved = SavedEcosystem {
+        seed,
+        level,
+        critters,
+        food_pellets,
+    };
+    persistence::save(SavedEcosystem::FILE_NAME, &saved);
+}
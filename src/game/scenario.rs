@@ -0,0 +1,56 @@
+//! The conditions a simulation run plays out under, like how long days and seasons last.
+//!
+//! Each [`GameMode`] comes with its own [`Scenario`], which is picked whenever the game mode is
+//! chosen on the level select screen.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::game::GameMode;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Scenario>();
+    app.init_resource::<Scenario>();
+    app.add_systems(Update, choose_scenario.run_if(resource_changed::<GameMode>));
+}
+
+#[derive(Resource, Debug, Clone, PartialEq, Reflect)]
+#[reflect(Resource)]
+pub struct Scenario {
+    /// How long a full day and night take in simulation time.
+    pub day_length: Duration,
+    /// How long each of the four seasons lasts in simulation time.
+    pub season_length: Duration,
+    /// How much harsher winter is than summer, between 0 (no seasons) and 1 (nothing grows or
+    /// reproduces in the middle of winter).
+    pub season_amplitude: f32,
+}
+
+impl Scenario {
+    pub fn for_mode(game_mode: GameMode) -> Self {
+        match game_mode {
+            GameMode::Sandbox => Self {
+                day_length: Duration::from_secs(240),
+                season_length: Duration::from_secs(600),
+                season_amplitude: 0.8,
+            },
+            // Short enough that a run sees a few nights and a whole year before the time limit.
+            GameMode::Survival => Self {
+                day_length: Duration::from_secs(90),
+                season_length: Duration::from_secs(75),
+                season_amplitude: 0.6,
+            },
+        }
+    }
+}
+
+impl Default for Scenario {
+    fn default() -> Self {
+        Self::for_mode(GameMode::default())
+    }
+}
+
+fn choose_scenario(mut commands: Commands, game_mode: Res<GameMode>) {
+    commands.insert_resource(Scenario::for_mode(*game_mode));
+}
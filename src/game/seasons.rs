@@ -0,0 +1,157 @@
+//! Seasons: food grows scarce, critters burn more energy and fewer offspring survive as the year
+//! turns from summer to winter, which keeps populations from settling into an equilibrium.
+//!
+//! How long a season lasts and how much harsher winter is than summer depend on the [`Scenario`].
+
+use std::f32::consts::TAU;
+
+use bevy::{prelude::*, ui::Val::*};
+
+use crate::{game::scenario::Scenario, screens::Screen, theme::prelude::*, AppSet};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Climate>();
+    app.register_type::<SeasonLabel>();
+    app.register_type::<SeasonProgress>();
+    app.init_resource::<Climate>();
+    app.add_systems(OnEnter(Screen::Gameplay), (start_year, spawn_season_hud));
+    app.add_systems(
+        Update,
+        (
+            advance_year.in_set(AppSet::TickTimers),
+            update_season_hud.in_set(AppSet::Update),
+        )
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+pub enum Season {
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+impl Season {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Spring => "Spring",
+            Self::Summer => "Summer",
+            Self::Autumn => "Autumn",
+            Self::Winter => "Winter",
+        }
+    }
+}
+
+#[derive(Resource, Debug, Default, Reflect)]
+#[reflect(Resource)]
+pub struct Climate {
+    /// How much of the year has passed, between 0 and 1. The year starts with spring.
+    pub time_of_year: f32,
+    /// How much harsher the middle of winter is than the middle of summer, between 0 and 1.
+    pub amplitude: f32,
+}
+
+impl Climate {
+    pub fn season(&self) -> Season {
+        match (self.time_of_year * 4.0) as u32 {
+            0 => Season::Spring,
+            1 => Season::Summer,
+            2 => Season::Autumn,
+            _ => Season::Winter,
+        }
+    }
+
+    /// How far into the current season the year is, between 0 and 1.
+    pub fn season_progress(&self) -> f32 {
+        (self.time_of_year * 4.0).fract()
+    }
+
+    /// How hard times are, between 0 in the middle of summer and [`Climate::amplitude`] in the
+    /// middle of winter.
+    pub fn harshness(&self) -> f32 {
+        // Summer is centered on 3/8 of the year, winter on 7/8.
+        let warmth = ((self.time_of_year - 0.375) * TAU).cos();
+        self.amplitude * (1.0 - warmth) / 2.0
+    }
+
+    /// How fast food grows compared to summer.
+    pub fn food_production(&self) -> f32 {
+        1.0 - self.harshness()
+    }
+
+    /// How much energy staying alive costs compared to summer.
+    pub fn metabolism_cost(&self) -> f32 {
+        1.0 + self.harshness()
+    }
+
+    /// The chance that a critter with enough energy to reproduce actually does.
+    pub fn reproduction_viability(&self) -> f32 {
+        1.0 - self.harshness()
+    }
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct SeasonLabel;
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct SeasonProgress;
+
+fn start_year(mut commands: Commands, scenario: Res<Scenario>) {
+    commands.insert_resource(Climate {
+        time_of_year: 0.0,
+        amplitude: scenario.season_amplitude.clamp(0.0, 1.0),
+    });
+}
+
+fn advance_year(time: Res<Time>, scenario: Res<Scenario>, mut climate: ResMut<Climate>) {
+    let years = time.delta_secs() / (scenario.season_length.as_secs_f32() * 4.0);
+    climate.time_of_year = (climate.time_of_year + years).fract();
+}
+
+fn spawn_season_hud(mut commands: Commands) {
+    commands
+        .spawn((
+            Name::new("Season HUD"),
+            Node {
+                position_type: PositionType::Absolute,
+                right: Px(10.0),
+                top: Px(10.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::End,
+                row_gap: Px(4.0),
+                ..default()
+            },
+            StateScoped(Screen::Gameplay),
+        ))
+        .with_children(|children| {
+            children.label(Season::Spring.name()).insert(SeasonLabel);
+            children.progress_bar(0.0).insert((
+                Node {
+                    width: Px(120.0),
+                    height: Px(8.0),
+                    ..default()
+                },
+                SeasonProgress,
+            ));
+        });
+}
+
+fn update_season_hud(
+    climate: Res<Climate>,
+    mut labels: Query<&mut Text, With<SeasonLabel>>,
+    mut progress_bars: Query<&mut ProgressBar, With<SeasonProgress>>,
+) {
+    let name = climate.season().name();
+    for mut text in &mut labels {
+        if text.0 != name {
+            text.0 = name.to_string();
+        }
+    }
+    for mut progress_bar in &mut progress_bars {
+        progress_bar.set_if_neq(ProgressBar(climate.season_progress()));
+    }
+}
@@ -0,0 +1,289 @@
+//! An incrementally updated spatial index for things that move around a lot.
+//!
+//! Rebuilding a KD-tree of every critter a few times a second stops scaling at a few hundred
+//! critters. A [`SpatialGrid`] instead buckets entities into square cells on the XZ plane, and each
+//! frame only touches the entities that moved. Most of them stay in their cell, so their position
+//! is just updated in place. Things that rarely move, like water sources, can stay in a KD-tree.
+
+use std::marker::PhantomData;
+
+use bevy::{ecs::entity::EntityHashMap, prelude::*, transform::TransformSystem, utils::HashMap};
+
+/// Keeps a [`SpatialGrid<T>`] of all entities with a `T` component.
+pub struct SpatialGridPlugin<T> {
+    cell_size: f32,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T: Component> SpatialGridPlugin<T> {
+    /// Queries are cheapest when `cell_size` is about half their typical radius.
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T: Component> Plugin for SpatialGridPlugin<T> {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SpatialGrid::<T>::new(self.cell_size));
+        // Right after transforms propagate, so that the grid is up to date for the next frame.
+        app.add_systems(
+            PostUpdate,
+            update_grid::<T>.after(TransformSystem::TransformPropagate),
+        );
+    }
+}
+
+/// The positions of all entities with a `T` component, bucketed into square cells.
+#[derive(Resource)]
+pub struct SpatialGrid<T> {
+    cell_size: f32,
+    cells: HashMap<IVec2, Vec<(Entity, Vec3)>>,
+    /// The cell each entity is in and its index there.
+    slots: EntityHashMap<(IVec2, usize)>,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> SpatialGrid<T> {
+    fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            cells: HashMap::default(),
+            slots: EntityHashMap::default(),
+            marker: PhantomData,
+        }
+    }
+
+    /// How many entities are in the grid.
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    /// All entities within `radius` of `point`, with their positions.
+    pub fn within_distance(
+        &self,
+        point: Vec3,
+        radius: f32,
+    ) -> impl Iterator<Item = (Vec3, Entity)> + '_ {
+        let min = self.cell(point - Vec3::splat(radius));
+        let max = self.cell(point + Vec3::splat(radius));
+        (min.x..=max.x)
+            .flat_map(move |x| (min.y..=max.y).map(move |z| IVec2::new(x, z)))
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .filter(move |(_, position)| position.distance_squared(point) <= radius * radius)
+            .map(|(entity, position)| (*position, *entity))
+    }
+
+    /// The entity nearest to `point`, if there is one within `radius`.
+    ///
+    /// Searches outwards ring by ring, so nearby entities are found without looking at the whole
+    /// radius.
+    pub fn nearest_within(&self, point: Vec3, radius: f32) -> Option<(Vec3, Entity)> {
+        let center = self.cell(point);
+        let rings = (radius / self.cell_size).ceil() as i32 + 1;
+        let mut nearest = None;
+        let mut nearest_distance = radius * radius;
+        for ring in 0..=rings {
+            // Nothing in this ring or beyond is closer than this on the XZ plane.
+            let ring_distance = (ring - 1).max(0) as f32 * self.cell_size;
+            if ring_distance * ring_distance > nearest_distance {
+                break;
+            }
+            let cells = (-ring..=ring)
+                .flat_map(|x| (-ring..=ring).map(move |z| IVec2::new(x, z)))
+                .filter(|offset| offset.abs().max_element() == ring)
+                .filter_map(|offset| self.cells.get(&(center + offset)));
+            for (entity, position) in cells.flatten() {
+                let distance = position.distance_squared(point);
+                if distance <= nearest_distance {
+                    nearest_distance = distance;
+                    nearest = Some((*position, *entity));
+                }
+            }
+        }
+        nearest
+    }
+
+    fn cell(&self, point: Vec3) -> IVec2 {
+        (point.xz() / self.cell_size).floor().as_ivec2()
+    }
+
+    fn insert(&mut self, entity: Entity, position: Vec3) {
+        let cell = self.cell(position);
+        match self.slots.get(&entity).copied() {
+            Some((old_cell, index)) if old_cell == cell => {
+                if let Some(entry) = self
+                    .cells
+                    .get_mut(&cell)
+                    .and_then(|entries| entries.get_mut(index))
+                {
+                    entry.1 = position;
+                }
+            }
+            Some(_) => {
+                self.remove(entity);
+                self.push(entity, cell, position);
+            }
+            None => self.push(entity, cell, position),
+        }
+    }
+
+    fn push(&mut self, entity: Entity, cell: IVec2, position: Vec3) {
+        let entries = self.cells.entry(cell).or_default();
+        self.slots.insert(entity, (cell, entries.len()));
+        entries.push((entity, position));
+    }
+
+    fn remove(&mut self, entity: Entity) {
+        let Some((cell, index)) = self.slots.remove(&entity) else {
+            return;
+        };
+        let Some(entries) = self.cells.get_mut(&cell) else {
+            return;
+        };
+        entries.swap_remove(index);
+        // The last entry took the removed one's place.
+        if let Some((moved, _)) = entries.get(index) {
+            self.slots.insert(*moved, (cell, index));
+        }
+        if entries.is_empty() {
+            self.cells.remove(&cell);
+        }
+    }
+}
+
+fn update_grid<T: Component>(
+    mut grid: ResMut<SpatialGrid<T>>,
+    moved: Query<(Entity, &GlobalTransform), (With<T>, Changed<GlobalTransform>)>,
+    mut removed: RemovedComponents<T>,
+) {
+    for entity in removed.read() {
+        grid.remove(entity);
+    }
+    for (entity, transform) in &moved {
+        grid.insert(entity, transform.translation());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Marker;
+
+    fn entity(index: u32) -> Entity {
+        Entity::from_raw(index)
+    }
+
+    fn cell_of(grid: &SpatialGrid<Marker>, entity: Entity) -> Option<IVec2> {
+        grid.slots.get(&entity).map(|(cell, _)| *cell)
+    }
+
+    /// Checks that every slot points at its entity, and that every entry has a slot.
+    fn assert_consistent(grid: &SpatialGrid<Marker>) {
+        for (entity, (cell, index)) in &grid.slots {
+            assert_eq!(grid.cells[cell][*index].0, *entity);
+        }
+        let entries = grid.cells.values().map(Vec::len).sum::<usize>();
+        assert_eq!(entries, grid.len());
+    }
+
+    #[test]
+    fn moving_across_a_cell_boundary_changes_cell() {
+        let mut grid = SpatialGrid::<Marker>::new(10.0);
+        grid.insert(entity(0), Vec3::new(9.0, 0.0, 5.0));
+        grid.insert(entity(1), Vec3::new(8.0, 0.0, 5.0));
+        assert_eq!(cell_of(&grid, entity(0)), Some(IVec2::new(0, 0)));
+
+        grid.insert(entity(0), Vec3::new(11.0, 0.0, 5.0));
+        assert_eq!(cell_of(&grid, entity(0)), Some(IVec2::new(1, 0)));
+        assert_eq!(grid.len(), 2);
+        assert_consistent(&grid);
+        assert_eq!(
+            grid.nearest_within(Vec3::new(12.0, 0.0, 5.0), 2.0),
+            Some((Vec3::new(11.0, 0.0, 5.0), entity(0)))
+        );
+        assert_eq!(
+            grid.within_distance(Vec3::new(9.0, 0.0, 5.0), 0.5).count(),
+            0
+        );
+    }
+
+    #[test]
+    fn removing_reindexes_the_swapped_entity() {
+        let mut grid = SpatialGrid::<Marker>::new(10.0);
+        for index in 0..3 {
+            grid.insert(entity(index), Vec3::new(index as f32, 0.0, 0.0));
+        }
+        grid.remove(entity(0));
+        assert_eq!(grid.len(), 2);
+        assert_consistent(&grid);
+
+        // The entity that took the removed one's place can still be moved and removed.
+        grid.insert(entity(2), Vec3::new(5.0, 0.0, 0.0));
+        assert_eq!(
+            grid.nearest_within(Vec3::new(5.0, 0.0, 0.0), 0.5),
+            Some((Vec3::new(5.0, 0.0, 0.0), entity(2)))
+        );
+        grid.remove(entity(2));
+        grid.remove(entity(1));
+        assert!(grid.is_empty());
+        assert!(grid.cells.is_empty());
+    }
+
+    #[test]
+    fn nearest_within_matches_brute_force() {
+        let mut grid = SpatialGrid::<Marker>::new(4.0);
+        let mut positions = Vec::new();
+        // A deterministic scatter over a few dozen cells.
+        for index in 0..200u32 {
+            let position = Vec3::new(
+                ((index * 37) % 101) as f32 * 0.37 - 18.0,
+                (index % 3) as f32,
+                ((index * 53) % 97) as f32 * 0.41 - 20.0,
+            );
+            grid.insert(entity(index), position);
+            positions.push((position, entity(index)));
+        }
+
+        let points = [
+            Vec3::ZERO,
+            Vec3::new(-17.5, 0.0, 19.0),
+            Vec3::new(30.0, 0.0, -30.0),
+            Vec3::new(3.9, 1.0, -4.1),
+        ];
+        for point in points {
+            // Several rings out, and small radii that may just reach the nearest entity.
+            for radius in [0.1, 0.5, 1.0, 2.5, 4.0, 9.0, 20.0, 60.0] {
+                let expected = positions
+                    .iter()
+                    .map(|(position, _)| position.distance_squared(point))
+                    .filter(|&distance| distance <= radius * radius)
+                    .min_by(f32::total_cmp);
+                let found = grid
+                    .nearest_within(point, radius)
+                    .map(|(position, _)| position.distance_squared(point));
+                assert_eq!(found, expected, "point {point}, radius {radius}");
+            }
+        }
+    }
+
+    #[test]
+    fn nearest_within_includes_the_radius_edge() {
+        let mut grid = SpatialGrid::<Marker>::new(1.0);
+        // Five cells away, so the search has to go several rings out.
+        grid.insert(entity(0), Vec3::new(3.0, 0.0, 4.0));
+        assert_eq!(
+            grid.nearest_within(Vec3::ZERO, 5.0),
+            Some((Vec3::new(3.0, 0.0, 4.0), entity(0)))
+        );
+        assert_eq!(grid.nearest_within(Vec3::ZERO, 4.99), None);
+    }
+}
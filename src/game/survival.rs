@@ -0,0 +1,215 @@
+//! Survival mode: the player is a single herbivore that wins by raising enough descendants or by
+//! outliving the time limit, and loses as soon as it perishes.
+
+use std::time::Duration;
+
+use bevy::{prelude::*, ui::Val::*, utils::HashSet};
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    game::{
+        critters::{CritterAte, CritterBorn, Energy, Herbivore},
+        possession::possess,
+        GameMode, Player, PlayerInputMap,
+    },
+    persistence,
+    screens::Screen,
+    theme::prelude::*,
+    AppSet,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<SurvivalRun>();
+    app.init_resource::<SurvivalRun>();
+    app.insert_resource(persistence::load::<HighScores>(HighScores::FILE_NAME));
+
+    app.add_systems(
+        OnEnter(Screen::Gameplay),
+        (start_run, spawn_hud).run_if(resource_equals(GameMode::Survival)),
+    );
+    app.add_systems(
+        Update,
+        (
+            tick_lifetime.in_set(AppSet::TickTimers),
+            (
+                possess_survivor,
+                count_food_eaten,
+                count_descendants,
+                update_hud,
+                check_outcome,
+            )
+                .chain()
+                .in_set(AppSet::Update),
+        )
+            .run_if(in_state(Screen::Gameplay).and(resource_equals(GameMode::Survival))),
+    );
+}
+
+/// Number of descendants the player's critter needs to win.
+pub const TARGET_DESCENDANTS: u32 = 10;
+/// Surviving this long also wins the game.
+pub const TIME_LIMIT: Duration = Duration::from_secs(300);
+
+/// Progress of the current (or most recent) survival run.
+#[derive(Resource, Debug, Default, Reflect)]
+#[reflect(Resource)]
+pub struct SurvivalRun {
+    /// The critter controlled by the player.
+    player: Option<Entity>,
+    /// The player's critter and all of its descendants.
+    #[reflect(ignore)]
+    lineage: HashSet<Entity>,
+    pub lifetime: Duration,
+    pub food_eaten: u32,
+    pub descendants: u32,
+    pub outcome: Option<Outcome>,
+}
+
+impl SurvivalRun {
+    pub fn score(&self) -> u32 {
+        self.descendants * 100 + self.food_eaten * 10 + self.lifetime.as_secs() as u32
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+pub enum Outcome {
+    Won,
+    Perished,
+}
+
+/// The best survival runs so far, persisted between sessions.
+#[derive(Resource, Debug, Default, Serialize, Deserialize)]
+pub struct HighScores(pub Vec<HighScore>);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HighScore {
+    pub score: u32,
+    pub lifetime_secs: f32,
+    pub food_eaten: u32,
+    pub descendants: u32,
+    pub won: bool,
+}
+
+impl HighScores {
+    const FILE_NAME: &'static str = "high_scores.ron";
+    const MAX_ENTRIES: usize = 10;
+
+    fn record(&mut self, run: &SurvivalRun) {
+        self.0.push(HighScore {
+            score: run.score(),
+            lifetime_secs: run.lifetime.as_secs_f32(),
+            food_eaten: run.food_eaten,
+            descendants: run.descendants,
+            won: run.outcome == Some(Outcome::Won),
+        });
+        self.0.sort_by(|a, b| b.score.cmp(&a.score));
+        self.0.truncate(Self::MAX_ENTRIES);
+    }
+}
+
+fn start_run(mut commands: Commands) {
+    commands.insert_resource(SurvivalRun::default());
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct SurvivalHud;
+
+fn spawn_hud(mut commands: Commands) {
+    commands
+        .spawn((
+            Name::new("Survival HUD"),
+            Node {
+                position_type: PositionType::Absolute,
+                left: Px(10.0),
+                top: Px(10.0),
+                ..default()
+            },
+            StateScoped(Screen::Gameplay),
+        ))
+        .with_children(|children| {
+            children.label("").insert(SurvivalHud);
+        });
+}
+
+fn tick_lifetime(time: Res<Time>, mut run: ResMut<SurvivalRun>) {
+    if run.player.is_some() {
+        run.lifetime += time.delta();
+    }
+}
+
+fn possess_survivor(
+    mut commands: Commands,
+    mut run: ResMut<SurvivalRun>,
+    herbivores: Query<Entity, With<Herbivore>>,
+    input_map: Res<PlayerInputMap>,
+) {
+    if run.player.is_some() {
+        return;
+    }
+    let Some(survivor) = herbivores.iter().choose(&mut thread_rng()) else {
+        return;
+    };
+    possess(&mut commands, survivor, &input_map);
+    run.player = Some(survivor);
+    run.lineage.insert(survivor);
+}
+
+fn count_food_eaten(mut ate: EventReader<CritterAte>, mut run: ResMut<SurvivalRun>) {
+    for event in ate.read() {
+        if run.player == Some(event.eater) {
+            run.food_eaten += 1;
+        }
+    }
+}
+
+fn count_descendants(mut born: EventReader<CritterBorn>, mut run: ResMut<SurvivalRun>) {
+    for event in born.read() {
+        if run.lineage.contains(&event.parent) {
+            run.lineage.insert(event.child);
+            run.descendants += 1;
+        }
+    }
+}
+
+fn update_hud(
+    run: Res<SurvivalRun>,
+    energy: Query<&Energy, With<Player>>,
+    mut hud: Query<&mut Text, With<SurvivalHud>>,
+) {
+    let energy = energy.get_single().map_or(0, |energy| energy.0);
+    for mut text in &mut hud {
+        text.0 = format!(
+            "Energy: {energy}\nLifetime: {}s / {}s\nFood eaten: {}\nDescendants: {} / {}",
+            run.lifetime.as_secs(),
+            TIME_LIMIT.as_secs(),
+            run.food_eaten,
+            run.descendants,
+            TARGET_DESCENDANTS,
+        );
+    }
+}
+
+fn check_outcome(
+    mut run: ResMut<SurvivalRun>,
+    players: Query<(), With<Player>>,
+    mut high_scores: ResMut<HighScores>,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    let Some(player) = run.player else {
+        return;
+    };
+    let outcome = if !players.contains(player) {
+        Outcome::Perished
+    } else if run.descendants >= TARGET_DESCENDANTS || run.lifetime >= TIME_LIMIT {
+        Outcome::Won
+    } else {
+        return;
+    };
+
+    run.outcome = Some(outcome);
+    high_scores.record(&run);
+    persistence::save(HighScores::FILE_NAME, &*high_scores);
+    next_screen.set(Screen::GameOver);
+}
@@ -0,0 +1,424 @@
+//! Procedurally generated terrain, as an alternative to the level authored in Blender.
+//!
+//! The terrain is a heightmap of layered value noise, seeded with the
+//! [`SimulationSeed`](super::level::SimulationSeed) so that the same seed always gives the same
+//! landscape. Its edges rise into hills that keep critters in. Everything below [`WATER_LEVEL`] is
+//! a lake that critters drink from, the land above it is divided into [`Biome`]s covered with
+//! [`FoodZone`]s, and rocks are scattered around as obstacles.
+
+use avian3d::prelude::*;
+use bevy::{
+    prelude::*,
+    render::{
+        mesh::{Indices, PrimitiveTopology},
+        render_asset::RenderAssetUsages,
+    },
+};
+use blenvy::GameWorldTag;
+use rand::prelude::*;
+
+use crate::{
+    game::{
+        biomes::{Biome, BiomeMap},
+        food::{FoodWeight, FoodZone},
+        water::WaterSource,
+    },
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Water>();
+}
+
+/// Vertices along each side of the terrain mesh.
+const RESOLUTION: usize = 128;
+/// How high the highest hills are, not counting the rim.
+const MAX_HEIGHT: f32 = 24.0;
+/// Typical distance between two hills.
+const FEATURE_SIZE: f32 = 60.0;
+const OCTAVES: u32 = 4;
+/// How high the terrain rises at its edges.
+const RIM_HEIGHT: f32 = 40.0;
+/// How far from the center the rim starts rising, as a share of the terrain's half size.
+const RIM_START: f32 = 0.8;
+/// Terrain below this height is under water.
+pub const WATER_LEVEL: f32 = 0.0;
+/// Fertile land is divided into square food zones this wide.
+const FOOD_ZONE_SIZE: f32 = 20.0;
+/// Fertility, between 0 and 1, above which land is forest...
+const FOREST_FERTILITY: f32 = 0.57;
+/// ...and below which it is desert.
+const DESERT_FERTILITY: f32 = 0.43;
+/// Fertile land lower than this is swamp.
+const SWAMP_HEIGHT: f32 = 3.0;
+/// Land higher than this is too rocky for plants.
+const TREE_LINE: f32 = 16.0;
+/// Rocks per square unit of terrain.
+const ROCK_DENSITY: f32 = 1.0 / 1500.0;
+
+const SAND_COLOR: Color = Color::srgb(0.76, 0.7, 0.5);
+const GRASS_COLOR: Color = Color::srgb(0.3, 0.55, 0.2);
+const FOREST_COLOR: Color = Color::srgb(0.15, 0.35, 0.12);
+const DESERT_COLOR: Color = Color::srgb(0.85, 0.72, 0.45);
+const SWAMP_COLOR: Color = Color::srgb(0.3, 0.34, 0.2);
+const ROCK_COLOR: Color = Color::srgb(0.5, 0.5, 0.52);
+const WATER_COLOR: Color = Color::srgba(0.15, 0.35, 0.6, 0.75);
+
+/// Marks a body of water.
+///
+/// Water has no collider on purpose: critters cross lakes by walking along the bottom, like they
+/// would cross a valley, instead of having to find a way around them.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct Water;
+
+/// The height and fertility of the terrain on a square grid centered on the origin. Inserted as a
+/// resource while a terrain level is played.
+#[derive(Resource, Clone)]
+pub struct Heightmap {
+    size: f32,
+    heights: Vec<f32>,
+    fertility: Vec<f32>,
+}
+
+impl Heightmap {
+    /// Generates `size` by `size` units of terrain.
+    pub fn generate(seed: u64, size: f32) -> Self {
+        let height_seed = seed as u32;
+        let fertility_seed = (seed >> 32) as u32 ^ 0x9e37_79b9;
+        let mut heightmap = Self {
+            size,
+            heights: Vec::with_capacity(RESOLUTION * RESOLUTION),
+            fertility: Vec::with_capacity(RESOLUTION * RESOLUTION),
+        };
+        for z in 0..RESOLUTION {
+            for x in 0..RESOLUTION {
+                let point = heightmap.grid_point(x, z);
+                let noise = fractal_noise(height_seed, point / FEATURE_SIZE);
+                let rim = ((point.abs().max_element() / (size / 2.0) - RIM_START)
+                    / (1.0 - RIM_START))
+                    .max(0.0);
+                heightmap
+                    .heights
+                    .push((noise - 0.35) * MAX_HEIGHT * 1.5 + rim * rim * RIM_HEIGHT);
+                heightmap
+                    .fertility
+                    .push(fractal_noise(fertility_seed, point / FEATURE_SIZE));
+            }
+        }
+        heightmap
+    }
+
+    pub fn size(&self) -> f32 {
+        self.size
+    }
+
+    /// The terrain height at a point on the XZ plane.
+    pub fn height_at(&self, point: Vec2) -> f32 {
+        self.sample(&self.heights, point)
+    }
+
+    /// How well plants grow at a point on the XZ plane, between 0 and 1.
+    pub fn fertility_at(&self, point: Vec2) -> f32 {
+        self.sample(&self.fertility, point)
+    }
+
+    /// The biome at a point on the XZ plane. Fertile lowlands are swamp, and the most and least
+    /// fertile land is forest and desert.
+    pub fn biome_at(&self, point: Vec2) -> Biome {
+        let fertility = self.fertility_at(point);
+        if fertility > 0.5 && self.height_at(point) < SWAMP_HEIGHT {
+            Biome::Swamp
+        } else if fertility > FOREST_FERTILITY {
+            Biome::Forest
+        } else if fertility < DESERT_FERTILITY {
+            Biome::Desert
+        } else {
+            Biome::Grassland
+        }
+    }
+
+    /// A random point on dry land inside the rim, at ground height.
+    pub fn random_land_point(&self, rng: &mut impl Rng) -> Vec3 {
+        let extent = self.size / 2.0 * RIM_START;
+        let mut point = Vec2::ZERO;
+        for _ in 0..100 {
+            point = Vec2::new(
+                rng.gen_range(-extent..extent),
+                rng.gen_range(-extent..extent),
+            );
+            if self.height_at(point) > WATER_LEVEL {
+                break;
+            }
+        }
+        Vec3::new(point.x, self.height_at(point), point.y)
+    }
+
+    fn index(x: usize, z: usize) -> usize {
+        z * RESOLUTION + x
+    }
+
+    fn cell_size(&self) -> f32 {
+        self.size / (RESOLUTION - 1) as f32
+    }
+
+    fn grid_point(&self, x: usize, z: usize) -> Vec2 {
+        Vec2::new(x as f32, z as f32) * self.cell_size() - self.size / 2.0
+    }
+
+    /// Bilinearly interpolates a grid of `values`. Points outside the grid are clamped to it.
+    fn sample(&self, values: &[f32], point: Vec2) -> f32 {
+        let last = (RESOLUTION - 1) as f32;
+        let grid =
+            ((point + self.size / 2.0) / self.cell_size()).clamp(Vec2::ZERO, Vec2::splat(last));
+        let (x, z) = (grid.x as usize, grid.y as usize);
+        let (x1, z1) = ((x + 1).min(RESOLUTION - 1), (z + 1).min(RESOLUTION - 1));
+        let t = grid.fract();
+        let near = values[Self::index(x, z)].lerp(values[Self::index(x1, z)], t.x);
+        let far = values[Self::index(x, z1)].lerp(values[Self::index(x1, z1)], t.x);
+        near.lerp(far, t.y)
+    }
+
+    fn normal(&self, x: usize, z: usize) -> Vec3 {
+        let height = |x: usize, z: usize| self.heights[Self::index(x, z)];
+        let (left, right) = (x.saturating_sub(1), (x + 1).min(RESOLUTION - 1));
+        let (back, front) = (z.saturating_sub(1), (z + 1).min(RESOLUTION - 1));
+        Vec3::new(
+            height(left, z) - height(right, z),
+            2.0 * self.cell_size(),
+            height(x, back) - height(x, front),
+        )
+        .normalize()
+    }
+
+    fn ground_color(&self, x: usize, z: usize) -> Color {
+        let height = self.heights[Self::index(x, z)];
+        if height < WATER_LEVEL + 1.0 {
+            SAND_COLOR
+        } else if height > TREE_LINE {
+            ROCK_COLOR
+        } else {
+            match self.biome_at(self.grid_point(x, z)) {
+                Biome::Grassland => GRASS_COLOR,
+                Biome::Forest => FOREST_COLOR,
+                Biome::Desert => DESERT_COLOR,
+                Biome::Swamp => SWAMP_COLOR,
+            }
+        }
+    }
+
+    fn mesh(&self) -> Mesh {
+        let mut positions = Vec::with_capacity(RESOLUTION * RESOLUTION);
+        let mut normals = Vec::with_capacity(RESOLUTION * RESOLUTION);
+        let mut colors = Vec::with_capacity(RESOLUTION * RESOLUTION);
+        let mut uvs = Vec::with_capacity(RESOLUTION * RESOLUTION);
+        for z in 0..RESOLUTION {
+            for x in 0..RESOLUTION {
+                let point = self.grid_point(x, z);
+                positions.push([point.x, self.heights[Self::index(x, z)], point.y]);
+                normals.push(self.normal(x, z).to_array());
+                colors.push(self.ground_color(x, z).to_linear().to_f32_array());
+                uvs.push([
+                    x as f32 / (RESOLUTION - 1) as f32,
+                    z as f32 / (RESOLUTION - 1) as f32,
+                ]);
+            }
+        }
+
+        let mut indices = Vec::with_capacity((RESOLUTION - 1) * (RESOLUTION - 1) * 6);
+        for z in 0..RESOLUTION - 1 {
+            for x in 0..RESOLUTION - 1 {
+                let corner = Self::index(x, z) as u32;
+                let right = corner + 1;
+                let front = corner + RESOLUTION as u32;
+                indices.extend([corner, front, right, right, front, front + 1]);
+            }
+        }
+
+        Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        )
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, colors)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+        .with_inserted_indices(Indices::U32(indices))
+    }
+}
+
+/// Spawns a terrain level and returns its heightmap, so that critters can be placed on it.
+///
+/// The terrain's root is the [`GameWorldTag`] entity, so it is also cleaned up with everything
+/// spawned with `AddToGameWorld`.
+pub fn spawn_terrain(world: &mut World, seed: u64, size: f32) -> Heightmap {
+    let heightmap = Heightmap::generate(seed, size);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let terrain_mesh = heightmap.mesh();
+    let terrain_collider =
+        Collider::trimesh_from_mesh(&terrain_mesh).expect("terrain mesh should be a triangle list");
+    let mut meshes = world.resource_mut::<Assets<Mesh>>();
+    let terrain_mesh = meshes.add(terrain_mesh);
+    let water_mesh = meshes.add(Plane3d::default().mesh().size(size, size));
+    let rock_mesh = meshes.add(Cuboid::default());
+    let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+    let terrain_material = materials.add(StandardMaterial {
+        perceptual_roughness: 0.9,
+        ..default()
+    });
+    let water_material = materials.add(StandardMaterial {
+        base_color: WATER_COLOR,
+        alpha_mode: AlphaMode::Blend,
+        perceptual_roughness: 0.1,
+        ..default()
+    });
+    let rock_material = materials.add(StandardMaterial {
+        base_color: ROCK_COLOR,
+        perceptual_roughness: 1.0,
+        ..default()
+    });
+
+    let rocks = (size * size * ROCK_DENSITY) as usize;
+    let rock_transforms = (0..rocks)
+        .map(|_| {
+            Transform::from_translation(heightmap.random_land_point(&mut rng))
+                .with_rotation(Quat::from_rotation_y(
+                    rng.gen_range(0.0..std::f32::consts::TAU),
+                ))
+                .with_scale(Vec3::new(
+                    rng.gen_range(2.0..6.0),
+                    rng.gen_range(1.5..5.0),
+                    rng.gen_range(2.0..6.0),
+                ))
+        })
+        .collect::<Vec<_>>();
+
+    let extent = size / 2.0 * RIM_START;
+    let cells = (0..)
+        .map(|i| -extent + FOOD_ZONE_SIZE * (i as f32 + 0.5))
+        .take_while(|&coordinate| coordinate < extent)
+        .collect::<Vec<_>>();
+    let cells = cells
+        .iter()
+        .flat_map(|&x| cells.iter().map(move |&z| Vec2::new(x, z)))
+        .collect::<Vec<_>>();
+    let food_zones = cells
+        .iter()
+        .copied()
+        .filter(|&center| {
+            let height = heightmap.height_at(center);
+            height > WATER_LEVEL && height < TREE_LINE
+        })
+        .collect::<Vec<_>>();
+    // Reaching out to the shores of the surrounding cells.
+    let water_sources = cells
+        .iter()
+        .copied()
+        .filter(|&center| heightmap.height_at(center) < WATER_LEVEL)
+        .collect::<Vec<_>>();
+    world.insert_resource(BiomeMap::from_fn(size, |point| heightmap.biome_at(point)));
+    world.insert_resource(heightmap.clone());
+
+    world
+        .spawn((
+            Name::new("Terrain"),
+            Transform::default(),
+            Visibility::default(),
+            GameWorldTag,
+            StateScoped(Screen::Gameplay),
+        ))
+        .with_children(|children| {
+            children.spawn((
+                Name::new("Ground"),
+                Mesh3d(terrain_mesh),
+                MeshMaterial3d(terrain_material),
+                RigidBody::Static,
+                terrain_collider,
+            ));
+            children.spawn((
+                Name::new("Water"),
+                Mesh3d(water_mesh),
+                MeshMaterial3d(water_material),
+                Transform::from_xyz(0.0, WATER_LEVEL, 0.0),
+                Water,
+            ));
+            children.spawn((
+                Name::new("Sun"),
+                DirectionalLight {
+                    shadows_enabled: true,
+                    ..default()
+                },
+            ));
+            for transform in rock_transforms {
+                children.spawn((
+                    Name::new("Rock"),
+                    Mesh3d(rock_mesh.clone()),
+                    MeshMaterial3d(rock_material.clone()),
+                    transform,
+                    RigidBody::Static,
+                    Collider::cuboid(1.0, 1.0, 1.0),
+                ));
+            }
+            for center in food_zones {
+                children.spawn((
+                    Name::new("Food zone"),
+                    FoodZone {
+                        half_size: Vec2::splat(FOOD_ZONE_SIZE / 2.0),
+                    },
+                    heightmap.biome_at(center),
+                    // Patches of fertile land within a biome get more food.
+                    FoodWeight(0.5 + heightmap.fertility_at(center)),
+                    Transform::from_xyz(center.x, heightmap.height_at(center), center.y),
+                ));
+            }
+            for center in water_sources {
+                children.spawn((
+                    Name::new("Water source"),
+                    WaterSource {
+                        radius: FOOD_ZONE_SIZE,
+                    },
+                    Transform::from_xyz(center.x, WATER_LEVEL, center.y),
+                ));
+            }
+        });
+
+    heightmap
+}
+
+/// Layers octaves of value noise, between 0 and 1.
+fn fractal_noise(seed: u32, point: Vec2) -> f32 {
+    let mut total = 0.0;
+    let mut max = 0.0;
+    let mut amplitude = 1.0;
+    let mut frequency = 1.0;
+    for octave in 0..OCTAVES {
+        total += value_noise(seed.wrapping_add(octave), point * frequency) * amplitude;
+        max += amplitude;
+        amplitude *= 0.5;
+        frequency *= 2.0;
+    }
+    total / max
+}
+
+/// Smoothly interpolated random values on an integer lattice, between 0 and 1.
+fn value_noise(seed: u32, point: Vec2) -> f32 {
+    let cell = point.floor();
+    let t = point - cell;
+    let t = t * t * (3.0 - 2.0 * t);
+    let (x, z) = (cell.x as i32, cell.y as i32);
+    let corner = |dx: i32, dz: i32| lattice_value(seed, x + dx, z + dz);
+    let near = corner(0, 0).lerp(corner(1, 0), t.x);
+    let far = corner(0, 1).lerp(corner(1, 1), t.x);
+    near.lerp(far, t.y)
+}
+
+/// A random value between 0 and 1 for a lattice point.
+fn lattice_value(seed: u32, x: i32, z: i32) -> f32 {
+    let mut hash =
+        seed ^ (x as u32).wrapping_mul(0x27d4_eb2d) ^ (z as u32).wrapping_mul(0x1656_67b1);
+    hash = (hash ^ (hash >> 15)).wrapping_mul(0x2c1b_3c6d);
+    hash = (hash ^ (hash >> 12)).wrapping_mul(0x297a_2d39);
+    hash ^= hash >> 15;
+    hash as f32 / u32::MAX as f32
+}
@@ -0,0 +1,168 @@
+//! Water and thirst: critters have to drink from [`WaterSource`]s every so often on top of
+//! eating, so they balance trips to the water against feeding.
+//!
+//! A heritable [`DroughtTolerance`] lets critters go longer between drinks, at the price of
+//! burning more energy.
+//!
+//! Generated terrain puts water sources along its lake. The handcrafted world gets a few ponds
+//! when it spawns, and more can be placed in Blender by adding a [`WaterSource`] component.
+
+use std::time::Duration;
+
+use bevy::{prelude::*, time::common_conditions::on_timer};
+use bevy_spatial::{kdtree::KDTree3, SpatialAccess};
+use rand::prelude::*;
+
+use crate::{
+    game::critters::{CritterDied, DeathCause},
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<WaterSource>();
+    app.register_type::<Hydration>();
+    app.register_type::<DroughtTolerance>();
+    app.add_systems(
+        Update,
+        (
+            drink.run_if(on_timer(Duration::from_millis(250))),
+            dehydrate.run_if(on_timer(Duration::from_secs(2))),
+        ),
+    );
+}
+
+/// How much water a critter can hold.
+pub const MAX_HYDRATION: u32 = 20;
+/// Critters with less water than this go looking for more.
+const THIRST_THRESHOLD: u32 = MAX_HYDRATION / 2;
+/// How much less water the most drought tolerant critters lose...
+const MAX_DROUGHT_SAVING: f32 = 0.6;
+/// ...and how much more energy they burn for it.
+const DROUGHT_TOLERANCE_METABOLISM: f32 = 0.5;
+
+const POND_COLOR: Color = Color::srgba(0.15, 0.35, 0.6, 0.8);
+
+/// A place critters can drink from: anywhere within `radius` of it on the XZ plane.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+pub struct WaterSource {
+    pub radius: f32,
+}
+
+/// How much water a critter has left. It dies of thirst when this runs out.
+#[derive(Component, Debug, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct Hydration(pub u32);
+
+impl Default for Hydration {
+    fn default() -> Self {
+        Self(MAX_HYDRATION)
+    }
+}
+
+impl Hydration {
+    pub fn is_thirsty(&self) -> bool {
+        self.0 < THIRST_THRESHOLD
+    }
+}
+
+/// Heritable resistance to thirst, between 0 and 1. Tolerant critters lose water slower but burn
+/// more energy.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+pub struct DroughtTolerance(pub f32);
+
+impl DroughtTolerance {
+    /// How much water the critter loses compared to one without any tolerance.
+    pub fn water_loss(&self) -> f32 {
+        1.0 - MAX_DROUGHT_SAVING * self.0
+    }
+
+    /// How much energy staying alive costs the critter compared to one without any tolerance.
+    pub fn metabolism_cost(&self) -> f32 {
+        1.0 + DROUGHT_TOLERANCE_METABOLISM * self.0
+    }
+
+    /// The tolerance of an offspring, which is close to its parent's.
+    pub fn inherit(&self, rng: &mut impl Rng) -> Self {
+        Self((self.0 + rng.gen_range(-0.1..0.1)).clamp(0.0, 1.0))
+    }
+}
+
+/// Spawns `count` ponds at random points within `extent` of the origin, for levels without water of
+/// their own.
+pub fn spawn_ponds(world: &mut World, rng: &mut impl Rng, count: usize, extent: f32) {
+    let mut meshes = world.resource_mut::<Assets<Mesh>>();
+    let mesh = meshes.add(Cylinder::new(1.0, 0.1));
+    let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+    let material = materials.add(StandardMaterial {
+        base_color: POND_COLOR,
+        alpha_mode: AlphaMode::Blend,
+        perceptual_roughness: 0.1,
+        ..default()
+    });
+    for _ in 0..count {
+        let radius = rng.gen_range(6.0..10.0);
+        let location = Vec3::new(
+            rng.gen_range(-extent..extent),
+            0.05,
+            rng.gen_range(-extent..extent),
+        );
+        world.spawn((
+            Name::new("Pond"),
+            Mesh3d(mesh.clone()),
+            MeshMaterial3d(material.clone()),
+            Transform::from_translation(location).with_scale(Vec3::new(radius, 1.0, radius)),
+            WaterSource { radius },
+            StateScoped(Screen::Gameplay),
+        ));
+    }
+}
+
+/// Refills critters that are close enough to water.
+fn drink(
+    mut critters: Query<(&GlobalTransform, &mut Hydration)>,
+    water_tree: Res<KDTree3<WaterSource>>,
+    water_sources: Query<&WaterSource>,
+) {
+    for (transform, mut hydration) in &mut critters {
+        let translation = transform.translation();
+        let Some((pos, Some(entity))) = water_tree.nearest_neighbour(translation) else {
+            continue;
+        };
+        let Ok(source) = water_sources.get(entity) else {
+            continue;
+        };
+        if pos.xz().distance(translation.xz()) <= source.radius {
+            hydration.set_if_neq(Hydration::default());
+        }
+    }
+}
+
+fn dehydrate(
+    mut commands: Commands,
+    mut critters: Query<(
+        Entity,
+        &mut Hydration,
+        Option<&DroughtTolerance>,
+        &GlobalTransform,
+    )>,
+    mut died: EventWriter<CritterDied>,
+) {
+    let mut rng = thread_rng();
+    for (entity, mut hydration, drought_tolerance, transform) in &mut critters {
+        if hydration.0 == 0 {
+            commands.entity(entity).despawn_recursive();
+            died.send(CritterDied {
+                critter: entity,
+                translation: transform.translation(),
+                cause: DeathCause::Thirst,
+            });
+        } else {
+            // Fractional losses are paid on average, by sometimes rounding up.
+            let loss = drought_tolerance.map_or(1.0, DroughtTolerance::water_loss);
+            let loss = loss.trunc() as u32 + u32::from(rng.gen_bool(loss.fract().into()));
+            hydration.0 = hydration.0.saturating_sub(loss);
+        }
+    }
+}
@@ -3,7 +3,9 @@ pub mod audio;
 #[cfg(feature = "dev")]
 mod dev_tools;
 mod game;
+mod persistence;
 mod screens;
+mod settings;
 mod theme;
 
 use std::time::Duration;
@@ -21,6 +23,8 @@ use bevy_tnua_avian3d::*;
 use blenvy::BlenvyPlugin;
 use smooth_bevy_cameras::{controllers::orbit::OrbitCameraPlugin, LookTransformPlugin};
 
+use crate::{game::spatial::SpatialGridPlugin, settings::Settings};
+
 pub struct AppPlugin;
 
 impl Plugin for AppPlugin {
@@ -31,6 +35,9 @@ impl Plugin for AppPlugin {
             (AppSet::TickTimers, AppSet::RecordInput, AppSet::Update).chain(),
         );
 
+        // Load the user's settings early so the window and audio start out configured.
+        let settings = Settings::load();
+
         // Spawn the main camera.
         // TODO: Not sure what this does?..
         app.add_systems(Startup, spawn_ui_camera);
@@ -51,6 +58,8 @@ impl Plugin for AppPlugin {
                         canvas: Some("#bevy".to_string()),
                         fit_canvas_to_parent: true,
                         prevent_default_event_handling: true,
+                        mode: settings.window_mode.window_mode(),
+                        present_mode: settings.present_mode(),
                         ..default()
                     }
                     .into(),
@@ -58,7 +67,7 @@ impl Plugin for AppPlugin {
                 })
                 .set(AudioPlugin {
                     global_volume: GlobalVolume {
-                        volume: Volume::new(0.3),
+                        volume: Volume::new(settings.master_volume),
                     },
                     ..default()
                 }),
@@ -69,24 +78,27 @@ impl Plugin for AppPlugin {
             // Camera
             LookTransformPlugin,
             OrbitCameraPlugin::default(),
-            // Herbivores
-            AutomaticUpdate::<crate::game::critters::FoodPellet>::new()
-                .with_spatial_ds(SpatialStructure::KDTree3)
-                .with_frequency(Duration::from_secs_f32(0.5))
-                .with_transform(TransformMode::GlobalTransform),
-            AutomaticUpdate::<crate::game::critters::Herbivore>::new()
+            // Food pellets and critters move around too much for a KD-tree to keep up with.
+            SpatialGridPlugin::<crate::game::critters::FoodPellet>::new(16.0),
+            SpatialGridPlugin::<crate::game::critters::Herbivore>::new(16.0),
+            SpatialGridPlugin::<crate::game::critters::Preditor>::new(16.0),
+            // Water doesn't move, but levels come and go.
+            AutomaticUpdate::<crate::game::water::WaterSource>::new()
                 .with_spatial_ds(SpatialStructure::KDTree3)
-                .with_frequency(Duration::from_secs_f32(0.5))
+                .with_frequency(Duration::from_secs(1))
                 .with_transform(TransformMode::GlobalTransform),
         ));
 
         app.register_type::<bevy::text::TextEntity>();
+        app.insert_resource(settings);
 
         // Add other plugins.
         app.add_plugins((
             asset_tracking::plugin,
+            audio::plugin,
             game::plugin,
             screens::plugin,
+            settings::plugin,
             theme::plugin,
         ));
 
@@ -0,0 +1,65 @@
+//! Saving small pieces of player data, such as high scores, between runs.
+//!
+//! Native builds store each value as a RON file in the [`SAVE_DIR`] directory.
+//! Web builds have no file system, so loading always falls back to the default value there.
+
+use serde::{de::DeserializeOwned, Serialize};
+
+/// The directory that persisted files are written to, relative to the working directory.
+pub const SAVE_DIR: &str = "saves";
+
+/// Loads a value previously written with [`save`], or its default if there is none.
+#[cfg(not(target_family = "wasm"))]
+pub fn load<T: DeserializeOwned + Default>(file_name: &str) -> T {
+    use bevy::log::warn;
+
+    let path = std::path::Path::new(SAVE_DIR).join(file_name);
+    match std::fs::read_to_string(&path) {
+        Ok(contents) => match ron::from_str(&contents) {
+            Ok(value) => return value,
+            Err(error) => warn!("Failed to parse {}: {error}", path.display()),
+        },
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => {}
+        Err(error) => warn!("Failed to read {}: {error}", path.display()),
+    }
+    T::default()
+}
+
+/// Writes a value to disk so it can be read back with [`load`] on the next run.
+#[cfg(not(target_family = "wasm"))]
+pub fn save<T: Serialize>(file_name: &str, value: &T) {
+    use bevy::log::warn;
+
+    let path = std::path::Path::new(SAVE_DIR).join(file_name);
+    let contents = match ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default()) {
+        Ok(contents) => contents,
+        Err(error) => {
+            warn!("Failed to serialize {}: {error}", path.display());
+            return;
+        }
+    };
+    if let Err(error) =
+        std::fs::create_dir_all(SAVE_DIR).and_then(|()| std::fs::write(&path, contents))
+    {
+        warn!("Failed to write {}: {error}", path.display());
+    }
+}
+
+/// Whether a value has been written with [`save`] before.
+#[cfg(not(target_family = "wasm"))]
+pub fn exists(file_name: &str) -> bool {
+    std::path::Path::new(SAVE_DIR).join(file_name).is_file()
+}
+
+#[cfg(target_family = "wasm")]
+pub fn load<T: DeserializeOwned + Default>(_file_name: &str) -> T {
+    T::default()
+}
+
+#[cfg(target_family = "wasm")]
+pub fn save<T: Serialize>(_file_name: &str, _value: &T) {}
+
+#[cfg(target_family = "wasm")]
+pub fn exists(_file_name: &str) -> bool {
+    false
+}
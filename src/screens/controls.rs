@@ -0,0 +1,211 @@
+//! A controls screen that can be accessed from the title screen, for rebinding the keyboard keys
+//! and gamepad buttons used to steer a possessed critter.
+
+use bevy::{prelude::*, ui::Val::*};
+
+use crate::{
+    game::{GamepadBindings, KeyBindings, PlayerAction},
+    persistence,
+    screens::Screen,
+    theme::{navigation::NavigationSet, prelude::*},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<RebindTarget>();
+    app.add_systems(OnEnter(Screen::Controls), spawn_controls_screen);
+    app.add_systems(OnExit(Screen::Controls), cancel_rebind);
+    // The button being bound must not also press the focused menu button.
+    app.configure_sets(Update, NavigationSet.run_if(not(rebinding_gamepad)));
+
+    app.add_systems(
+        Update,
+        (
+            // After navigation may have started a rebind, so that it is skipped for that frame.
+            rebind.after(NavigationSet),
+            update_binding_labels.run_if(
+                resource_changed::<KeyBindings>
+                    .or(resource_changed::<GamepadBindings>)
+                    .or(resource_changed::<RebindTarget>),
+            ),
+        )
+            .chain()
+            .run_if(in_state(Screen::Controls)),
+    );
+}
+
+/// One of the inputs an action can be bound to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BindingSlot {
+    /// One of the [`KeyBindings::SLOTS`] keyboard keys.
+    Key(usize),
+    Gamepad,
+}
+
+/// The action and slot that will be bound to the next key or button press, if any.
+#[derive(Resource, Default)]
+struct RebindTarget(Option<(PlayerAction, BindingSlot)>);
+
+#[derive(Component)]
+struct BindingLabel(PlayerAction);
+
+fn spawn_controls_screen(
+    mut commands: Commands,
+    key_bindings: Res<KeyBindings>,
+    gamepad_bindings: Res<GamepadBindings>,
+) {
+    commands
+        .ui_root()
+        .insert((Name::new("Controls screen"), StateScoped(Screen::Controls)))
+        .with_children(|children| {
+            children.header("Controls");
+            for action in PlayerAction::ALL {
+                children
+                    .spawn((
+                        Name::new("Binding"),
+                        Node {
+                            align_items: AlignItems::Center,
+                            column_gap: Px(10.0),
+                            ..default()
+                        },
+                    ))
+                    .with_children(|row| {
+                        row.label(binding_text(action, &key_bindings, &gamepad_bindings, None))
+                            .insert(BindingLabel(action));
+                        for slot in 0..KeyBindings::SLOTS {
+                            row.small_button(format!("Key {}", slot + 1))
+                                .observe(start_rebind(action, BindingSlot::Key(slot)));
+                        }
+                        row.small_button("Gamepad")
+                            .observe(start_rebind(action, BindingSlot::Gamepad));
+                    });
+            }
+            children.label("Rebinding a slot keeps the action's other keys.");
+            children.label("Inputs used by another action are swapped with it.");
+            children.label("The left stick always moves as well.");
+
+            children.button("Reset").observe(reset_bindings);
+            children
+                .button("Back")
+                .insert(BackButton)
+                .observe(enter_title_screen);
+        });
+}
+
+fn binding_text(
+    action: PlayerAction,
+    key_bindings: &KeyBindings,
+    gamepad_bindings: &GamepadBindings,
+    target: Option<(PlayerAction, BindingSlot)>,
+) -> String {
+    match target {
+        Some((target, BindingSlot::Key(slot))) if target == action => {
+            return format!(
+                "{}: press a key for key {} (Escape to cancel)",
+                action.label(),
+                slot + 1
+            );
+        }
+        Some((target, BindingSlot::Gamepad)) if target == action => {
+            return format!(
+                "{}: press a gamepad button (Escape to cancel)",
+                action.label()
+            );
+        }
+        _ => {}
+    }
+    let keys = (0..KeyBindings::SLOTS)
+        .map(|slot| {
+            key_bindings
+                .get(action, slot)
+                .map_or("-".to_string(), |key| format!("{key:?}"))
+        })
+        .collect::<Vec<_>>()
+        .join(" / ");
+    let button = gamepad_bindings
+        .0
+        .get(&action)
+        .map_or("-".to_string(), |button| format!("{button:?}"));
+    format!("{}: {keys} / {button}", action.label())
+}
+
+fn start_rebind(
+    action: PlayerAction,
+    slot: BindingSlot,
+) -> impl Fn(Trigger<OnPress>, ResMut<RebindTarget>) {
+    move |_trigger, mut target| target.0 = Some((action, slot))
+}
+
+fn cancel_rebind(mut target: ResMut<RebindTarget>) {
+    target.0 = None;
+}
+
+fn rebinding_gamepad(target: Res<RebindTarget>) -> bool {
+    matches!(target.0, Some((_, BindingSlot::Gamepad)))
+}
+
+fn rebind(
+    keys: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    mut target: ResMut<RebindTarget>,
+    mut key_bindings: ResMut<KeyBindings>,
+    mut gamepad_bindings: ResMut<GamepadBindings>,
+) {
+    let Some((action, slot)) = target.0 else {
+        return;
+    };
+    // The button that started the rebind, like South on a gamepad, is still just pressed in the
+    // frame it starts in. Only the next press counts.
+    if target.is_changed() {
+        return;
+    }
+    if keys.just_pressed(KeyCode::Escape) {
+        target.0 = None;
+        return;
+    }
+    match slot {
+        BindingSlot::Key(slot) => {
+            let Some(&key) = keys.get_just_pressed().next() else {
+                return;
+            };
+            key_bindings.bind(action, slot, key);
+            persistence::save(KeyBindings::FILE_NAME, &*key_bindings);
+        }
+        BindingSlot::Gamepad => {
+            let Some(&button) = gamepads
+                .iter()
+                .find_map(|gamepad| gamepad.get_just_pressed().next())
+            else {
+                return;
+            };
+            gamepad_bindings.bind(action, button);
+            persistence::save(GamepadBindings::FILE_NAME, &*gamepad_bindings);
+        }
+    }
+    target.0 = None;
+}
+
+fn update_binding_labels(
+    key_bindings: Res<KeyBindings>,
+    gamepad_bindings: Res<GamepadBindings>,
+    target: Res<RebindTarget>,
+    mut labels: Query<(&BindingLabel, &mut Text)>,
+) {
+    for (label, mut text) in &mut labels {
+        text.0 = binding_text(label.0, &key_bindings, &gamepad_bindings, target.0);
+    }
+}
+
+fn reset_bindings(
+    _trigger: Trigger<OnPress>,
+    mut key_bindings: ResMut<KeyBindings>,
+    mut gamepad_bindings: ResMut<GamepadBindings>,
+) {
+    *key_bindings = KeyBindings::default();
+    *gamepad_bindings = GamepadBindings::default();
+    persistence::save(KeyBindings::FILE_NAME, &*key_bindings);
+    persistence::save(GamepadBindings::FILE_NAME, &*gamepad_bindings);
+}
+
+fn enter_title_screen(_trigger: Trigger<OnPress>, mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Title);
+}
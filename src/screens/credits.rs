@@ -2,14 +2,19 @@
 
 use bevy::prelude::*;
 
-use crate::{asset_tracking::LoadResource, audio::Music, screens::Screen, theme::prelude::*};
+use crate::{
+    audio::playlist::{AddMusic, Playlist},
+    screens::Screen,
+    theme::prelude::*,
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(OnEnter(Screen::Credits), spawn_credits_screen);
 
-    app.load_resource::<CreditsMusic>();
-    app.add_systems(OnEnter(Screen::Credits), play_credits_music);
-    app.add_systems(OnExit(Screen::Credits), stop_music);
+    app.add_music(
+        Screen::Credits,
+        Playlist::new(["audio/music/Monkeys Spinning Monkeys.ogg"]).looping(),
+    );
 }
 
 fn spawn_credits_screen(mut commands: Commands) {
@@ -27,45 +32,13 @@ fn spawn_credits_screen(mut commands: Commands) {
             children.label("Button SFX - CC0 by Jaszunio15");
             children.label("Music - CC BY 3.0 by Kevin MacLeod");
 
-            children.button("Back").observe(enter_title_screen);
+            children
+                .button("Back")
+                .insert(BackButton)
+                .observe(enter_title_screen);
         });
 }
 
 fn enter_title_screen(_trigger: Trigger<OnPress>, mut next_screen: ResMut<NextState<Screen>>) {
     next_screen.set(Screen::Title);
 }
-
-#[derive(Resource, Asset, Reflect, Clone)]
-pub struct CreditsMusic {
-    #[dependency]
-    music: Handle<AudioSource>,
-    entity: Option<Entity>,
-}
-
-impl FromWorld for CreditsMusic {
-    fn from_world(world: &mut World) -> Self {
-        let assets = world.resource::<AssetServer>();
-        Self {
-            music: assets.load("audio/music/Monkeys Spinning Monkeys.ogg"),
-            entity: None,
-        }
-    }
-}
-
-fn play_credits_music(mut commands: Commands, mut music: ResMut<CreditsMusic>) {
-    music.entity = Some(
-        commands
-            .spawn((
-                AudioPlayer(music.music.clone()),
-                PlaybackSettings::LOOP,
-                Music,
-            ))
-            .id(),
-    );
-}
-
-fn stop_music(mut commands: Commands, mut music: ResMut<CreditsMusic>) {
-    if let Some(entity) = music.entity.take() {
-        commands.entity(entity).despawn_recursive();
-    }
-}
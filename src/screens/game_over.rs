@@ -0,0 +1,65 @@
+//! The screen shown when a survival run ends, with its score and the high-score table.
+
+use bevy::prelude::*;
+
+use crate::{
+    game::{
+        critters::DeathTally,
+        survival::{HighScores, Outcome, SurvivalRun},
+    },
+    screens::Screen,
+    theme::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::GameOver), spawn_game_over_screen);
+}
+
+fn spawn_game_over_screen(
+    mut commands: Commands,
+    run: Res<SurvivalRun>,
+    high_scores: Res<HighScores>,
+    death_tally: Res<DeathTally>,
+) {
+    commands
+        .ui_root()
+        .insert((Name::new("Game over screen"), StateScoped(Screen::GameOver)))
+        .with_children(|children| {
+            children.header(match run.outcome {
+                Some(Outcome::Won) => "Your dynasty thrives!",
+                _ => "You perished",
+            });
+            children.label(format!("Score: {}", run.score()));
+            children.label(format!("Lifetime: {}s", run.lifetime.as_secs()));
+            children.label(format!("Food eaten: {}", run.food_eaten));
+            children.label(format!("Descendants: {}", run.descendants));
+            children.label(format!("Deaths: {}", death_tally.summary()));
+
+            children.header("High scores");
+            for (rank, entry) in high_scores.0.iter().enumerate() {
+                children.label(format!(
+                    "{}. {} - {:.0}s, {} food, {} descendants{}",
+                    rank + 1,
+                    entry.score,
+                    entry.lifetime_secs,
+                    entry.food_eaten,
+                    entry.descendants,
+                    if entry.won { " (won)" } else { "" },
+                ));
+            }
+
+            children.button("Play again").observe(enter_gameplay_screen);
+            children
+                .button("Title")
+                .insert(BackButton)
+                .observe(enter_title_screen);
+        });
+}
+
+fn enter_gameplay_screen(_trigger: Trigger<OnPress>, mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Gameplay);
+}
+
+fn enter_title_screen(_trigger: Trigger<OnPress>, mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Title);
+}
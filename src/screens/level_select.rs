@@ -0,0 +1,192 @@
+//! The level select screen, between the title screen and gameplay. Lists every level in the
+//! [`LevelRegistry`] with its preview and description, and starts it in the chosen game mode.
+
+use bevy::{prelude::*, ui::Val::*};
+
+use crate::{
+    game::{
+        level::{LevelInfo, LevelRegistry, SelectedLevel},
+        GameMode,
+    },
+    screens::{loading::enter_after_loading, Screen},
+    theme::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::LevelSelect), spawn_level_select_screen);
+    app.add_systems(
+        Update,
+        update_level_details
+            .run_if(in_state(Screen::LevelSelect).and(resource_changed::<SelectedLevel>)),
+    );
+}
+
+const PREVIEW_WIDTH: f32 = 320.0;
+const PREVIEW_HEIGHT: f32 = 180.0;
+
+/// Selects the level with this id.
+#[derive(Component, Debug)]
+struct LevelButton(&'static str);
+
+#[derive(Component, Debug)]
+enum LevelDetail {
+    Preview,
+    Name,
+    Description,
+    RecommendedMode,
+}
+
+fn spawn_level_select_screen(
+    mut commands: Commands,
+    registry: Res<LevelRegistry>,
+    mut selected: ResMut<SelectedLevel>,
+) {
+    // Levels can disappear between runs.
+    if registry.get(&selected.0).is_none() {
+        if let Some(level) = registry.iter().next() {
+            selected.0 = level.id.to_string();
+        }
+    }
+    // Fills in the details once the screen has spawned.
+    selected.set_changed();
+    commands
+        .ui_root()
+        .insert((
+            Name::new("Level select screen"),
+            StateScoped(Screen::LevelSelect),
+        ))
+        .with_children(|children| {
+            children.header("Choose a level");
+            children
+                .spawn((
+                    Name::new("Levels"),
+                    Node {
+                        column_gap: Px(30.0),
+                        ..default()
+                    },
+                ))
+                .with_children(|columns| {
+                    columns
+                        .spawn((
+                            Name::new("Level list"),
+                            Node {
+                                flex_direction: FlexDirection::Column,
+                                row_gap: Px(10.0),
+                                ..default()
+                            },
+                        ))
+                        .with_children(|list| {
+                            for level in registry.iter() {
+                                list.small_button(level.name)
+                                    .insert((
+                                        LevelButton(level.id),
+                                        Outline::new(Px(2.0), Px(0.0), Color::NONE),
+                                    ))
+                                    .observe(select_level(level.id));
+                            }
+                        });
+                    columns
+                        .spawn((
+                            Name::new("Level details"),
+                            Node {
+                                flex_direction: FlexDirection::Column,
+                                row_gap: Px(10.0),
+                                ..default()
+                            },
+                        ))
+                        .with_children(|details| {
+                            details.spawn((
+                                Name::new("Level preview"),
+                                Node {
+                                    width: Px(PREVIEW_WIDTH),
+                                    height: Px(PREVIEW_HEIGHT),
+                                    ..default()
+                                },
+                                ImageNode::default(),
+                                LevelDetail::Preview,
+                            ));
+                            details.label("").insert(LevelDetail::Name);
+                            details.label("").insert(LevelDetail::Description);
+                            details.label("").insert(LevelDetail::RecommendedMode);
+                        });
+                });
+            children
+                .spawn((
+                    Name::new("Game modes"),
+                    Node {
+                        column_gap: Px(10.0),
+                        ..default()
+                    },
+                ))
+                .with_children(|row| {
+                    row.button("Sandbox")
+                        .observe(start_level(GameMode::Sandbox));
+                    row.button("Survival")
+                        .observe(start_level(GameMode::Survival));
+                });
+            children
+                .button("Back")
+                .insert(BackButton)
+                .observe(enter_title_screen);
+        });
+}
+
+fn update_level_details(
+    registry: Res<LevelRegistry>,
+    selected: Res<SelectedLevel>,
+    asset_server: Res<AssetServer>,
+    mut buttons: Query<(&LevelButton, &mut Outline)>,
+    mut previews: Query<&mut ImageNode, With<LevelDetail>>,
+    mut texts: Query<(&LevelDetail, &mut Text)>,
+) {
+    let Some(level) = registry.get(&selected.0) else {
+        return;
+    };
+    for (button, mut outline) in &mut buttons {
+        outline.color = if button.0 == level.id {
+            ui_palette::SELECTED_OUTLINE
+        } else {
+            Color::NONE
+        };
+    }
+    for mut image in &mut previews {
+        // Levels without a preview get a blank panel instead.
+        *image = match level.preview {
+            Some(path) => ImageNode::new(asset_server.load(path)),
+            None => ImageNode::default().with_color(ui_palette::NODE_BACKGROUND),
+        };
+    }
+    for (detail, mut text) in &mut texts {
+        text.0 = match detail {
+            LevelDetail::Preview => continue,
+            LevelDetail::Name => level.name.to_string(),
+            LevelDetail::Description => level.description.to_string(),
+            LevelDetail::RecommendedMode => recommendation(level),
+        };
+    }
+}
+
+fn recommendation(level: &LevelInfo) -> String {
+    let mode = match level.recommended_mode {
+        GameMode::Sandbox => "Sandbox",
+        GameMode::Survival => "Survival",
+    };
+    format!("Recommended: {mode}")
+}
+
+fn select_level(id: &'static str) -> impl Fn(Trigger<OnPress>, ResMut<SelectedLevel>) {
+    move |_trigger, mut selected| {
+        selected.0 = id.to_string();
+    }
+}
+
+fn start_level(mode: GameMode) -> impl Fn(Trigger<OnPress>, Commands, ResMut<GameMode>) {
+    move |_trigger, mut commands, mut game_mode| {
+        *game_mode = mode;
+        commands.queue(enter_after_loading(Screen::Gameplay));
+    }
+}
+
+fn enter_title_screen(_trigger: Trigger<OnPress>, mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Title);
+}
@@ -1,22 +1,55 @@
 //! A loading screen during which game assets are loaded.
 //! This reduces stuttering, especially for audio on WASM.
+//!
+//! The screen waits for every requested [`ResourceGroup`](crate::asset_tracking::ResourceGroup),
+//! then continues to the [`LoadingTarget`].
 
 use bevy::prelude::*;
 
 use crate::{
-    screens::{credits::CreditsMusic, gameplay::GameplayMusic, Screen},
-    theme::{interaction::InteractionAssets, prelude::*},
+    asset_tracking::{all_resources_loaded, ResourceHandles},
+    screens::Screen,
+    theme::{palette::ERROR_TEXT, prelude::*},
 };
 
 pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<LoadingTarget>();
     app.add_systems(OnEnter(Screen::Loading), spawn_loading_screen);
+    app.add_systems(
+        Update,
+        (update_progress_bar, show_failed_assets).run_if(in_state(Screen::Loading)),
+    );
 
     app.add_systems(
         Update,
-        continue_to_title_screen.run_if(in_state(Screen::Loading).and(all_assets_loaded)),
+        continue_to_target_screen.run_if(in_state(Screen::Loading).and(all_resources_loaded)),
     );
 }
 
+/// The screen to continue to once loading has finished.
+#[derive(Resource, Debug)]
+pub(super) struct LoadingTarget(pub Screen);
+
+impl Default for LoadingTarget {
+    fn default() -> Self {
+        Self(Screen::Title)
+    }
+}
+
+/// A [`Command`] that enters `screen` right away if all requested resources are loaded, or shows
+/// the loading screen until they are.
+pub(super) fn enter_after_loading(screen: Screen) -> impl Command {
+    move |world: &mut World| {
+        let screen = if world.resource::<ResourceHandles>().is_all_loaded() {
+            screen
+        } else {
+            world.insert_resource(LoadingTarget(screen));
+            Screen::Loading
+        };
+        world.resource_mut::<NextState<Screen>>().set(screen);
+    }
+}
+
 fn spawn_loading_screen(mut commands: Commands) {
     commands
         .ui_root()
@@ -26,19 +59,67 @@ fn spawn_loading_screen(mut commands: Commands) {
                 justify_content: JustifyContent::Center,
                 ..default()
             });
+            children.progress_bar(0.0);
         });
 }
 
-fn continue_to_title_screen(mut next_screen: ResMut<NextState<Screen>>) {
-    next_screen.set(Screen::Title);
+fn update_progress_bar(
+    resource_handles: Res<ResourceHandles>,
+    mut progress_bars: Query<&mut ProgressBar>,
+) {
+    let fraction = resource_handles.progress().fraction();
+    for mut progress_bar in &mut progress_bars {
+        progress_bar.set_if_neq(ProgressBar(fraction));
+    }
 }
 
-fn all_assets_loaded(
-    interaction_assets: Option<Res<InteractionAssets>>,
-    credits_music: Option<Res<CreditsMusic>>,
-    gameplay_music: Option<Res<GameplayMusic>>,
-) -> bool {
-    interaction_assets.is_some()
-        && credits_music.is_some()
-        && gameplay_music.is_some()
+/// Lists the assets that failed to load, since the game can't leave the loading screen without
+/// them.
+fn show_failed_assets(
+    mut commands: Commands,
+    resource_handles: Res<ResourceHandles>,
+    panels: Query<(Entity, &FailedAssetsPanel)>,
+) {
+    let failed = resource_handles.failed();
+    if failed.is_empty() || panels.iter().any(|(_, panel)| panel.0 == failed.len()) {
+        return;
+    }
+    for (entity, _) in &panels {
+        commands.entity(entity).despawn_recursive();
+    }
+    commands
+        .spawn((
+            Name::new("Failed assets"),
+            FailedAssetsPanel(failed.len()),
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(10.0),
+                width: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(10.0),
+                ..default()
+            },
+            StateScoped(Screen::Loading),
+        ))
+        .with_children(|children| {
+            children.header("Some assets failed to load");
+            for asset in failed {
+                children
+                    .label(format!("{}: {}", asset.path, asset.error))
+                    .insert(TextColor(ERROR_TEXT));
+            }
+        });
+}
+
+/// Holds the number of failed assets listed, so the panel can be rebuilt when more fail.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct FailedAssetsPanel(usize);
+
+fn continue_to_target_screen(
+    mut target: ResMut<LoadingTarget>,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    next_screen.set(std::mem::take(&mut *target).0);
 }
@@ -1,21 +1,39 @@
 //! The game's main screen states and transitions between them.
 
+mod controls;
 mod credits;
+mod game_over;
 mod gameplay;
+mod level_select;
 mod loading;
+mod pause;
+mod settings;
 mod splash;
 mod title;
 
 use bevy::prelude::*;
 
+use crate::theme::navigation::NavigationSet;
+
 pub(super) fn plugin(app: &mut App) {
     app.init_state::<Screen>();
     app.enable_state_scoped_entities::<Screen>();
 
+    // The gamepad controls the critter and camera during gameplay, unless the game is paused.
+    app.configure_sets(
+        Update,
+        NavigationSet.run_if(not(in_state(Screen::Gameplay)).or(not(in_state(PauseMenu::Closed)))),
+    );
+
     app.add_plugins((
+        controls::plugin,
         credits::plugin,
+        game_over::plugin,
         gameplay::plugin,
+        level_select::plugin,
         loading::plugin,
+        pause::plugin,
+        settings::plugin,
         splash::plugin,
         title::plugin,
     ));
@@ -28,6 +46,25 @@ pub enum Screen {
     Splash,
     Loading,
     Title,
+    LevelSelect,
+    Controls,
+    Settings,
     Credits,
     Gameplay,
+    /// Passes straight back into [`Screen::Gameplay`], so that the level is torn down and
+    /// spawned again.
+    Restart,
+    GameOver,
+}
+
+/// The menus that can be opened on top of [`Screen::Gameplay`]. The simulation is paused while
+/// any of them is open.
+#[derive(SubStates, Debug, Hash, PartialEq, Eq, Clone, Copy, Default)]
+#[source(Screen = Screen::Gameplay)]
+pub enum PauseMenu {
+    #[default]
+    Closed,
+    Main,
+    Settings,
+    ConfirmQuit,
 }
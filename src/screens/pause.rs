@@ -0,0 +1,159 @@
+//! The pause menu that opens on top of the gameplay screen when pressing Escape or Start.
+
+use avian3d::prelude::*;
+use bevy::{input::common_conditions::input_just_pressed, prelude::*};
+
+use crate::{
+    game::{critters::DeathTally, save::save_ecosystem, GameMode},
+    screens::{
+        settings::{save_settings, settings_panel},
+        PauseMenu, Screen,
+    },
+    settings::Settings,
+    theme::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_sub_state::<PauseMenu>();
+    app.enable_state_scoped_entities::<PauseMenu>();
+
+    app.add_systems(OnExit(PauseMenu::Closed), pause_simulation);
+    app.add_systems(OnEnter(PauseMenu::Closed), resume_simulation);
+    // Quitting from the pause menu leaves gameplay without closing the menu first.
+    app.add_systems(OnExit(Screen::Gameplay), resume_simulation);
+
+    app.add_systems(OnEnter(PauseMenu::Main), spawn_pause_menu);
+    app.add_systems(OnEnter(PauseMenu::Settings), spawn_settings_menu);
+    app.add_systems(OnExit(PauseMenu::Settings), save_settings);
+    app.add_systems(OnEnter(PauseMenu::ConfirmQuit), spawn_quit_confirmation);
+
+    app.add_systems(
+        Update,
+        (
+            set_pause_menu(PauseMenu::Main).run_if(in_state(PauseMenu::Closed).and(pause_pressed)),
+            set_pause_menu(PauseMenu::Closed).run_if(in_state(PauseMenu::Main).and(pause_pressed)),
+            set_pause_menu(PauseMenu::Main).run_if(
+                in_state(PauseMenu::Settings)
+                    .or(in_state(PauseMenu::ConfirmQuit))
+                    .and(input_just_pressed(KeyCode::Escape)),
+            ),
+        ),
+    );
+}
+
+fn pause_pressed(keyboard: Res<ButtonInput<KeyCode>>, gamepads: Query<&Gamepad>) -> bool {
+    keyboard.just_pressed(KeyCode::Escape)
+        || gamepads
+            .iter()
+            .any(|gamepad| gamepad.just_pressed(GamepadButton::Start))
+}
+
+fn pause_simulation(mut time: ResMut<Time<Virtual>>, mut physics_time: ResMut<Time<Physics>>) {
+    time.pause();
+    physics_time.pause();
+}
+
+fn resume_simulation(mut time: ResMut<Time<Virtual>>, mut physics_time: ResMut<Time<Physics>>) {
+    time.unpause();
+    physics_time.unpause();
+}
+
+/// Shows whether the ecosystem was saved since the pause menu opened.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct SaveStatus;
+
+fn spawn_pause_menu(
+    mut commands: Commands,
+    game_mode: Res<GameMode>,
+    death_tally: Res<DeathTally>,
+) {
+    commands
+        .overlay_root()
+        .insert((Name::new("Pause menu"), StateScoped(PauseMenu::Main)))
+        .with_children(|children| {
+            children.header("Paused");
+            children.label(format!("Deaths: {}", death_tally.summary()));
+            children
+                .button("Resume")
+                .insert(BackButton)
+                .observe(open_pause_menu(PauseMenu::Closed));
+            children
+                .button("Settings")
+                .observe(open_pause_menu(PauseMenu::Settings));
+            // Saving in the middle of a survival run would allow retrying it from any point.
+            if *game_mode == GameMode::Sandbox {
+                children.button("Save").observe(save_game);
+            }
+            children.button("Restart").observe(restart);
+            children
+                .button("Quit")
+                .observe(open_pause_menu(PauseMenu::ConfirmQuit));
+            children.label("").insert(SaveStatus);
+        });
+}
+
+fn spawn_settings_menu(mut commands: Commands, settings: Res<Settings>) {
+    commands
+        .overlay_root()
+        .insert((
+            Name::new("Pause settings"),
+            StateScoped(PauseMenu::Settings),
+        ))
+        .with_children(|children| {
+            children.header("Settings");
+            settings_panel(children, &settings);
+            children
+                .button("Back")
+                .insert(BackButton)
+                .observe(open_pause_menu(PauseMenu::Main));
+        });
+}
+
+fn spawn_quit_confirmation(mut commands: Commands, game_mode: Res<GameMode>) {
+    commands
+        .overlay_root()
+        .insert((
+            Name::new("Quit confirmation"),
+            StateScoped(PauseMenu::ConfirmQuit),
+        ))
+        .with_children(|children| {
+            children.header("Quit to title?");
+            children.label(match *game_mode {
+                GameMode::Sandbox => "Anything that happened since the last save will be lost.",
+                GameMode::Survival => "This run will not count towards the high scores.",
+            });
+            children.button("Quit").observe(quit_to_title);
+            children
+                .button("Cancel")
+                .insert(BackButton)
+                .observe(open_pause_menu(PauseMenu::Main));
+        });
+}
+
+fn set_pause_menu(menu: PauseMenu) -> impl Fn(ResMut<NextState<PauseMenu>>) {
+    move |mut next_menu| next_menu.set(menu)
+}
+
+fn open_pause_menu(menu: PauseMenu) -> impl Fn(Trigger<OnPress>, ResMut<NextState<PauseMenu>>) {
+    move |_trigger, mut next_menu| next_menu.set(menu)
+}
+
+fn save_game(
+    _trigger: Trigger<OnPress>,
+    mut commands: Commands,
+    mut status: Query<&mut Text, With<SaveStatus>>,
+) {
+    commands.queue(save_ecosystem);
+    for mut text in &mut status {
+        text.0 = "Ecosystem saved.".to_string();
+    }
+}
+
+fn restart(_trigger: Trigger<OnPress>, mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Restart);
+}
+
+fn quit_to_title(_trigger: Trigger<OnPress>, mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Title);
+}
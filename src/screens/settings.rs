@@ -0,0 +1,188 @@
+//! A settings screen that can be accessed from the title screen.
+
+use bevy::{prelude::*, ui::Val::*};
+
+use crate::{
+    screens::Screen,
+    settings::{Settings, ShadowQuality, WindowModeSetting},
+    theme::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Settings), spawn_settings_screen);
+    app.add_systems(OnExit(Screen::Settings), save_settings);
+
+    app.add_systems(
+        Update,
+        update_setting_labels.run_if(resource_changed::<Settings>),
+    );
+}
+
+fn spawn_settings_screen(mut commands: Commands, settings: Res<Settings>) {
+    commands
+        .ui_root()
+        .insert((Name::new("Settings screen"), StateScoped(Screen::Settings)))
+        .with_children(|children| {
+            children.header("Settings");
+            settings_panel(children, &settings);
+            children
+                .button("Back")
+                .insert(BackButton)
+                .observe(enter_title_screen);
+        });
+}
+
+/// Spawns one row per setting, each with a label and a widget that edits it.
+pub(super) fn settings_panel(children: &mut ChildBuilder, settings: &Settings) {
+    for setting in SettingRow::ALL {
+        children
+            .spawn((
+                Name::new("Setting"),
+                Node {
+                    align_items: AlignItems::Center,
+                    column_gap: Px(10.0),
+                    ..default()
+                },
+            ))
+            .with_children(|row| {
+                row.label(setting.text(settings)).insert(setting);
+                match setting {
+                    SettingRow::MasterVolume => {
+                        row.slider(settings.master_volume, 0.0, 1.0, VOLUME_STEP)
+                            .observe(set_master_volume);
+                    }
+                    SettingRow::MusicVolume => {
+                        row.slider(settings.music_volume, 0.0, 1.0, VOLUME_STEP)
+                            .observe(set_music_volume);
+                    }
+                    SettingRow::SfxVolume => {
+                        row.slider(settings.sfx_volume, 0.0, 1.0, VOLUME_STEP)
+                            .observe(set_sfx_volume);
+                    }
+                    SettingRow::WindowMode => {
+                        row.dropdown(
+                            options(&WindowModeSetting::ALL, WindowModeSetting::label),
+                            index_of(&WindowModeSetting::ALL, settings.window_mode),
+                        )
+                        .observe(set_window_mode);
+                    }
+                    SettingRow::Vsync => {
+                        row.toggle(settings.vsync).observe(set_vsync);
+                    }
+                    SettingRow::ShadowQuality => {
+                        row.dropdown(
+                            options(&ShadowQuality::ALL, ShadowQuality::label),
+                            index_of(&ShadowQuality::ALL, settings.shadow_quality),
+                        )
+                        .observe(set_shadow_quality);
+                    }
+                    SettingRow::SimulationSpeed => {
+                        row.spinner(
+                            settings.simulation_speed,
+                            MIN_SIMULATION_SPEED,
+                            MAX_SIMULATION_SPEED,
+                            SIMULATION_SPEED_STEP,
+                        )
+                        .observe(set_simulation_speed);
+                    }
+                }
+            });
+    }
+}
+
+/// One line on the settings screen, also used to find its label.
+#[derive(Component, Debug, Clone, Copy)]
+enum SettingRow {
+    MasterVolume,
+    MusicVolume,
+    SfxVolume,
+    WindowMode,
+    Vsync,
+    ShadowQuality,
+    SimulationSpeed,
+}
+
+const VOLUME_STEP: f32 = 0.05;
+const MIN_SIMULATION_SPEED: f32 = 0.25;
+const MAX_SIMULATION_SPEED: f32 = 4.0;
+const SIMULATION_SPEED_STEP: f32 = 0.25;
+
+impl SettingRow {
+    const ALL: [Self; 7] = [
+        Self::MasterVolume,
+        Self::MusicVolume,
+        Self::SfxVolume,
+        Self::WindowMode,
+        Self::Vsync,
+        Self::ShadowQuality,
+        Self::SimulationSpeed,
+    ];
+
+    fn text(self, settings: &Settings) -> String {
+        match self {
+            Self::MasterVolume => format!("Master volume: {:.0}%", settings.master_volume * 100.0),
+            Self::MusicVolume => format!("Music volume: {:.0}%", settings.music_volume * 100.0),
+            Self::SfxVolume => format!("Sound effects volume: {:.0}%", settings.sfx_volume * 100.0),
+            Self::WindowMode => "Window mode".to_string(),
+            Self::Vsync => "VSync".to_string(),
+            Self::ShadowQuality => "Shadows".to_string(),
+            Self::SimulationSpeed => format!("Simulation speed: {}x", settings.simulation_speed),
+        }
+    }
+}
+
+fn options<T: Copy>(values: &[T], label: fn(T) -> &'static str) -> Vec<String> {
+    values
+        .iter()
+        .map(|value| label(*value).to_string())
+        .collect()
+}
+
+fn index_of<T: PartialEq>(values: &[T], value: T) -> usize {
+    values
+        .iter()
+        .position(|option| *option == value)
+        .unwrap_or_default()
+}
+
+fn set_master_volume(trigger: Trigger<ValueChanged<f32>>, mut settings: ResMut<Settings>) {
+    settings.master_volume = trigger.event().0;
+}
+
+fn set_music_volume(trigger: Trigger<ValueChanged<f32>>, mut settings: ResMut<Settings>) {
+    settings.music_volume = trigger.event().0;
+}
+
+fn set_sfx_volume(trigger: Trigger<ValueChanged<f32>>, mut settings: ResMut<Settings>) {
+    settings.sfx_volume = trigger.event().0;
+}
+
+fn set_window_mode(trigger: Trigger<ValueChanged<usize>>, mut settings: ResMut<Settings>) {
+    settings.window_mode = WindowModeSetting::ALL[trigger.event().0];
+}
+
+fn set_vsync(trigger: Trigger<ValueChanged<bool>>, mut settings: ResMut<Settings>) {
+    settings.vsync = trigger.event().0;
+}
+
+fn set_shadow_quality(trigger: Trigger<ValueChanged<usize>>, mut settings: ResMut<Settings>) {
+    settings.shadow_quality = ShadowQuality::ALL[trigger.event().0];
+}
+
+fn set_simulation_speed(trigger: Trigger<ValueChanged<f32>>, mut settings: ResMut<Settings>) {
+    settings.simulation_speed = trigger.event().0;
+}
+
+fn update_setting_labels(settings: Res<Settings>, mut labels: Query<(&SettingRow, &mut Text)>) {
+    for (setting, mut text) in &mut labels {
+        text.0 = setting.text(&settings);
+    }
+}
+
+pub(super) fn save_settings(settings: Res<Settings>) {
+    settings.save();
+}
+
+fn enter_title_screen(_trigger: Trigger<OnPress>, mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Title);
+}
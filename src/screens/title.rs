@@ -2,7 +2,11 @@
 
 use bevy::prelude::*;
 
-use crate::{screens::Screen, theme::prelude::*};
+use crate::{
+    game::{save::SavedEcosystem, GameMode},
+    screens::{loading::enter_after_loading, Screen},
+    theme::prelude::*,
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(OnEnter(Screen::Title), spawn_title_screen);
@@ -13,7 +17,12 @@ fn spawn_title_screen(mut commands: Commands) {
         .ui_root()
         .insert(StateScoped(Screen::Title))
         .with_children(|children| {
-            children.button("Play").observe(enter_gameplay_screen);
+            if SavedEcosystem::exists() {
+                children.button("Continue").observe(continue_saved_game);
+            }
+            children.button("Play").observe(enter_level_select_screen);
+            children.button("Controls").observe(enter_controls_screen);
+            children.button("Settings").observe(enter_settings_screen);
             children.button("Credits").observe(enter_credits_screen);
 
             #[cfg(not(target_family = "wasm"))]
@@ -21,8 +30,29 @@ fn spawn_title_screen(mut commands: Commands) {
         });
 }
 
-fn enter_gameplay_screen(_trigger: Trigger<OnPress>, mut next_screen: ResMut<NextState<Screen>>) {
-    next_screen.set(Screen::Gameplay);
+fn enter_level_select_screen(
+    _trigger: Trigger<OnPress>,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    next_screen.set(Screen::LevelSelect);
+}
+
+fn continue_saved_game(
+    _trigger: Trigger<OnPress>,
+    mut commands: Commands,
+    mut game_mode: ResMut<GameMode>,
+) {
+    commands.insert_resource(SavedEcosystem::load());
+    *game_mode = GameMode::Sandbox;
+    commands.queue(enter_after_loading(Screen::Gameplay));
+}
+
+fn enter_controls_screen(_trigger: Trigger<OnPress>, mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Controls);
+}
+
+fn enter_settings_screen(_trigger: Trigger<OnPress>, mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Settings);
 }
 
 fn enter_credits_screen(_trigger: Trigger<OnPress>, mut next_screen: ResMut<NextState<Screen>>) {
@@ -0,0 +1,234 @@
+//! User settings for audio, graphics and the simulation.
+//!
+//! [`Settings`] is loaded from disk when the app starts and applied whenever it changes.
+//! Screens that edit it are responsible for saving it again with [`Settings::save`].
+
+use bevy::{
+    audio::Volume,
+    pbr::DirectionalLightShadowMap,
+    prelude::*,
+    window::{PresentMode, PrimaryWindow, WindowMode},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    audio::{Fade, Music, SoundEffect},
+    persistence,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Settings>();
+    app.add_systems(
+        Update,
+        (
+            apply_volume,
+            apply_shadow_quality,
+            (apply_window_settings, apply_simulation_speed).run_if(resource_changed::<Settings>),
+        ),
+    );
+}
+
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize, Reflect)]
+#[reflect(Resource)]
+#[serde(default)]
+pub struct Settings {
+    /// Multiplies all other volumes.
+    pub master_volume: f32,
+    /// Volume of everything tagged [`Music`].
+    pub music_volume: f32,
+    /// Volume of everything tagged [`SoundEffect`].
+    pub sfx_volume: f32,
+    pub window_mode: WindowModeSetting,
+    pub vsync: bool,
+    pub shadow_quality: ShadowQuality,
+    /// Relative speed of virtual time, which drives the whole simulation.
+    pub simulation_speed: f32,
+}
+
+impl Settings {
+    pub const FILE_NAME: &'static str = "settings.ron";
+
+    pub fn load() -> Self {
+        persistence::load(Self::FILE_NAME)
+    }
+
+    pub fn save(&self) {
+        persistence::save(Self::FILE_NAME, self);
+    }
+
+    pub fn present_mode(&self) -> PresentMode {
+        if self.vsync {
+            PresentMode::AutoVsync
+        } else {
+            PresentMode::AutoNoVsync
+        }
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            master_volume: 0.3,
+            music_volume: 1.0,
+            sfx_volume: 1.0,
+            window_mode: WindowModeSetting::default(),
+            vsync: true,
+            shadow_quality: ShadowQuality::default(),
+            simulation_speed: 1.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, Reflect)]
+pub enum WindowModeSetting {
+    #[default]
+    Windowed,
+    Borderless,
+    Fullscreen,
+}
+
+impl WindowModeSetting {
+    pub const ALL: [Self; 3] = [Self::Windowed, Self::Borderless, Self::Fullscreen];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Windowed => "Windowed",
+            Self::Borderless => "Borderless",
+            Self::Fullscreen => "Fullscreen",
+        }
+    }
+
+    pub fn window_mode(self) -> WindowMode {
+        match self {
+            Self::Windowed => WindowMode::Windowed,
+            Self::Borderless => WindowMode::BorderlessFullscreen(MonitorSelection::Current),
+            Self::Fullscreen => WindowMode::Fullscreen(MonitorSelection::Current),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, Reflect)]
+pub enum ShadowQuality {
+    Off,
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
+impl ShadowQuality {
+    pub const ALL: [Self; 4] = [Self::Off, Self::Low, Self::Medium, Self::High];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Off => "Off",
+            Self::Low => "Low",
+            Self::Medium => "Medium",
+            Self::High => "High",
+        }
+    }
+
+    /// Size of the directional light shadow map, or `None` if shadows are disabled.
+    fn shadow_map_size(self) -> Option<usize> {
+        match self {
+            Self::Off => None,
+            Self::Low => Some(1024),
+            Self::Medium => Some(2048),
+            Self::High => Some(4096),
+        }
+    }
+}
+
+/// Sets the volume of all playing sounds, including ones that just started.
+///
+/// This overrides the volume the sounds were spawned with, so the categories stay consistent.
+/// The volume in a sound's [`PlaybackSettings`] and its [`Fade`] are kept as factors on top.
+fn apply_volume(
+    settings: Res<Settings>,
+    mut global_volume: ResMut<GlobalVolume>,
+    sinks: Query<(Ref<AudioSink>, SoundCategory)>,
+    spatial_sinks: Query<(Ref<SpatialAudioSink>, SoundCategory)>,
+) {
+    if settings.is_changed() {
+        global_volume.volume = Volume::new(settings.master_volume);
+    }
+    for (sink, category) in &sinks {
+        if settings.is_changed() || sink.is_added() || is_fading(category.1.as_ref()) {
+            sink.set_volume(category_volume(&settings, category));
+        }
+    }
+    for (sink, category) in &spatial_sinks {
+        if settings.is_changed() || sink.is_added() || is_fading(category.1.as_ref()) {
+            sink.set_volume(category_volume(&settings, category));
+        }
+    }
+}
+
+/// The [`PlaybackSettings`] and [`Fade`] of a sound and whether it is tagged [`Music`] or
+/// [`SoundEffect`].
+type SoundCategory = (
+    Option<&'static PlaybackSettings>,
+    Option<Ref<'static, Fade>>,
+    Has<Music>,
+    Has<SoundEffect>,
+);
+
+fn is_fading(fade: Option<&Ref<Fade>>) -> bool {
+    fade.is_some_and(DetectChanges::is_changed)
+}
+
+fn category_volume(
+    settings: &Settings,
+    (playback, fade, is_music, is_sound_effect): (
+        Option<&PlaybackSettings>,
+        Option<Ref<Fade>>,
+        bool,
+        bool,
+    ),
+) -> f32 {
+    let category = if is_music {
+        settings.music_volume
+    } else if is_sound_effect {
+        settings.sfx_volume
+    } else {
+        1.0
+    };
+    let own = playback.map_or(1.0, |playback| playback.volume.get());
+    let fade = fade.map_or(1.0, |fade| fade.volume);
+    settings.master_volume * category * own * fade
+}
+
+/// Applies the shadow quality to the shadow map and all directional lights, including ones that
+/// were just spawned by a level.
+fn apply_shadow_quality(
+    settings: Res<Settings>,
+    mut shadow_map: ResMut<DirectionalLightShadowMap>,
+    mut lights: Query<&mut DirectionalLight>,
+) {
+    let size = settings.shadow_quality.shadow_map_size();
+    if settings.is_changed() {
+        if let Some(size) = size {
+            shadow_map.size = size;
+        }
+    }
+    for mut light in &mut lights {
+        if settings.is_changed() || light.is_added() {
+            light.shadows_enabled = size.is_some();
+        }
+    }
+}
+
+fn apply_window_settings(
+    settings: Res<Settings>,
+    mut window: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    let Ok(mut window) = window.get_single_mut() else {
+        return;
+    };
+    window.mode = settings.window_mode.window_mode();
+    window.present_mode = settings.present_mode();
+}
+
+fn apply_simulation_speed(settings: Res<Settings>, mut time: ResMut<Time<Virtual>>) {
+    time.set_relative_speed(settings.simulation_speed);
+}
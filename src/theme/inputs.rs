@@ -0,0 +1,415 @@
+//! State and behavior of the value widgets spawned through [`Widgets`](super::widgets::Widgets):
+//! sliders, toggles, dropdowns, spinners and text inputs.
+//!
+//! Each of them triggers a [`ValueChanged`] event on its own entity when the user changes its
+//! value, so screens can observe it the same way they observe [`OnPress`] on buttons:
+//!
+//! ```ignore
+//! children.slider(0.5, 0.0, 1.0, 0.1).observe(set_volume);
+//!
+//! fn set_volume(trigger: Trigger<ValueChanged<f32>>, mut settings: ResMut<Settings>) {
+//!     settings.volume = trigger.0;
+//! }
+//! ```
+
+use bevy::{
+    input::{
+        keyboard::{Key, KeyboardInput},
+        ButtonState,
+    },
+    prelude::*,
+    ui::{RelativeCursorPosition, Val::*},
+};
+
+use crate::theme::{interaction::OnPress, palette::*, widgets::Widgets};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Slider>();
+    app.register_type::<Toggle>();
+    app.register_type::<Dropdown>();
+    app.register_type::<Spinner>();
+    app.register_type::<TextInput>();
+    app.add_systems(
+        Update,
+        (
+            drag_slider,
+            (stop_editing_on_click_outside, edit_text).chain(),
+            (
+                update_slider_fill,
+                update_toggle_indicator,
+                update_dropdown_text,
+                update_spinner_text,
+                update_text_input_text,
+            ),
+        )
+            .chain(),
+    );
+}
+
+/// Spinners with finer steps than this show them rounded.
+const MAX_SPINNER_DECIMALS: usize = 4;
+
+/// Event triggered on a value widget when the user changes its value.
+#[derive(Event, Debug)]
+pub struct ValueChanged<T>(pub T);
+
+/// A horizontal bar that can be dragged to pick a value between `min` and `max`.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+pub struct Slider {
+    pub value: f32,
+    pub min: f32,
+    pub max: f32,
+    /// Values are rounded to multiples of this, starting at `min`.
+    pub step: f32,
+}
+
+impl Slider {
+    /// How far along the bar the current value is, between 0 and 1.
+    pub fn fraction(&self) -> f32 {
+        // A slider without a range is always full, rather than dividing by zero.
+        if self.max <= self.min {
+            return 1.0;
+        }
+        ((self.value - self.min) / (self.max - self.min)).clamp(0.0, 1.0)
+    }
+
+    fn value_at(&self, fraction: f32) -> f32 {
+        let value = self.min + fraction.clamp(0.0, 1.0) * (self.max - self.min);
+        let value = if self.step > 0.0 {
+            self.min + ((value - self.min) / self.step).round() * self.step
+        } else {
+            value
+        };
+        value.clamp(self.min, self.max)
+    }
+}
+
+/// A checkbox that flips between on and off when pressed.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+pub struct Toggle(pub bool);
+
+/// A button showing the selected option that opens a list of all options when pressed.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+pub struct Dropdown {
+    pub options: Vec<String>,
+    pub selected: usize,
+    /// The list of options while it is open.
+    list: Option<Entity>,
+}
+
+impl Dropdown {
+    pub fn new(options: Vec<String>, selected: usize) -> Self {
+        Self {
+            options,
+            selected,
+            list: None,
+        }
+    }
+}
+
+/// A number with buttons to step it down and up within `min` and `max`.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+pub struct Spinner {
+    pub value: f32,
+    pub min: f32,
+    pub max: f32,
+    pub step: f32,
+}
+
+/// A single line of editable text. Press it to start typing, press Enter or click elsewhere to stop.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+pub struct TextInput {
+    pub text: String,
+    pub max_length: usize,
+}
+
+/// Marks the [`TextInput`] that receives keyboard input.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+pub struct Editing;
+
+/// Marks the child node of a widget that displays its value.
+#[derive(Component, Debug)]
+pub(super) struct ValueDisplay;
+
+fn drag_slider(
+    mut commands: Commands,
+    mut sliders: Query<(Entity, &Interaction, &RelativeCursorPosition, &mut Slider)>,
+) {
+    for (entity, interaction, cursor, mut slider) in &mut sliders {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let Some(cursor) = cursor.normalized else {
+            continue;
+        };
+        let value = slider.value_at(cursor.x);
+        if value != slider.value {
+            slider.value = value;
+            commands.trigger_targets(ValueChanged(value), entity);
+        }
+    }
+}
+
+pub(super) fn toggle_on_press(
+    trigger: Trigger<OnPress>,
+    mut commands: Commands,
+    mut toggles: Query<&mut Toggle>,
+) {
+    let entity = trigger.entity();
+    if let Ok(mut toggle) = toggles.get_mut(entity) {
+        toggle.0 = !toggle.0;
+        commands.trigger_targets(ValueChanged(toggle.0), entity);
+    }
+}
+
+pub(super) fn open_dropdown_on_press(
+    trigger: Trigger<OnPress>,
+    mut commands: Commands,
+    mut dropdowns: Query<&mut Dropdown>,
+) {
+    let entity = trigger.entity();
+    let Ok(mut dropdown) = dropdowns.get_mut(entity) else {
+        return;
+    };
+    if let Some(list) = dropdown.list.take() {
+        commands.entity(list).despawn_recursive();
+        return;
+    }
+
+    let list = commands
+        .spawn((
+            Name::new("Dropdown List"),
+            Node {
+                position_type: PositionType::Absolute,
+                top: Percent(100.0),
+                left: Px(0.0),
+                min_width: Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            // Draw the list on top of everything, including overlays the dropdown is part of.
+            GlobalZIndex(i32::MAX),
+        ))
+        .with_children(|list| {
+            for (index, option) in dropdown.options.iter().enumerate() {
+                list.small_button(option.clone())
+                    .observe(select_dropdown_option(entity, index));
+            }
+        })
+        .set_parent(entity)
+        .id();
+    dropdown.list = Some(list);
+}
+
+fn select_dropdown_option(
+    dropdown: Entity,
+    index: usize,
+) -> impl Fn(Trigger<OnPress>, Commands, Query<&mut Dropdown>) {
+    move |_trigger, mut commands, mut dropdowns| {
+        let Ok(mut state) = dropdowns.get_mut(dropdown) else {
+            return;
+        };
+        if let Some(list) = state.list.take() {
+            commands.entity(list).despawn_recursive();
+        }
+        if state.selected != index {
+            state.selected = index;
+            commands.trigger_targets(ValueChanged(index), dropdown);
+        }
+    }
+}
+
+/// Steps a [`Spinner`] by `direction` (`-1` or `1`) times its step.
+pub(super) fn step_spinner(
+    spinner: Entity,
+    direction: f32,
+) -> impl Fn(Trigger<OnPress>, Commands, Query<&mut Spinner>) {
+    move |_trigger, mut commands, mut spinners| {
+        let Ok(mut state) = spinners.get_mut(spinner) else {
+            return;
+        };
+        let value = (state.value + direction * state.step).clamp(state.min, state.max);
+        if value != state.value {
+            state.value = value;
+            commands.trigger_targets(ValueChanged(value), spinner);
+        }
+    }
+}
+
+pub(super) fn edit_text_on_press(
+    trigger: Trigger<OnPress>,
+    mut commands: Commands,
+    editing: Query<Entity, With<Editing>>,
+) {
+    for entity in &editing {
+        commands.entity(entity).remove::<Editing>();
+    }
+    commands.entity(trigger.entity()).insert(Editing);
+}
+
+fn stop_editing_on_click_outside(
+    mut commands: Commands,
+    mouse: Res<ButtonInput<MouseButton>>,
+    editing: Query<(Entity, &Interaction), With<Editing>>,
+) {
+    if !mouse.just_pressed(MouseButton::Left) {
+        return;
+    }
+    for (entity, interaction) in &editing {
+        if *interaction == Interaction::None {
+            commands.entity(entity).remove::<Editing>();
+        }
+    }
+}
+
+fn edit_text(
+    mut commands: Commands,
+    mut keyboard: EventReader<KeyboardInput>,
+    mut editing: Query<(Entity, &mut TextInput), With<Editing>>,
+) {
+    let Ok((entity, mut input)) = editing.get_single_mut() else {
+        keyboard.clear();
+        return;
+    };
+    let mut changed = false;
+    for event in keyboard.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+        match &event.logical_key {
+            Key::Character(characters) => {
+                for character in characters.chars() {
+                    if input.text.chars().count() < input.max_length {
+                        input.text.push(character);
+                        changed = true;
+                    }
+                }
+            }
+            Key::Space if input.text.chars().count() < input.max_length => {
+                input.text.push(' ');
+                changed = true;
+            }
+            Key::Backspace => {
+                changed |= input.text.pop().is_some();
+            }
+            Key::Enter | Key::Escape => {
+                commands.entity(entity).remove::<Editing>();
+            }
+            _ => {}
+        }
+    }
+    if changed {
+        commands.trigger_targets(ValueChanged(input.text.clone()), entity);
+    }
+}
+
+fn update_slider_fill(
+    sliders: Query<(&Slider, &Children), Changed<Slider>>,
+    mut fills: Query<&mut Node, With<ValueDisplay>>,
+) {
+    for (slider, children) in &sliders {
+        let mut fills = fills.iter_many_mut(children);
+        while let Some(mut fill) = fills.fetch_next() {
+            fill.width = Percent(slider.fraction() * 100.0);
+        }
+    }
+}
+
+fn update_toggle_indicator(
+    toggles: Query<(&Toggle, &Children), Changed<Toggle>>,
+    mut indicators: Query<&mut BackgroundColor, With<ValueDisplay>>,
+) {
+    for (toggle, children) in &toggles {
+        let mut indicators = indicators.iter_many_mut(children);
+        while let Some(mut indicator) = indicators.fetch_next() {
+            *indicator = toggle_color(toggle.0).into();
+        }
+    }
+}
+
+fn update_dropdown_text(
+    dropdowns: Query<(&Dropdown, &Children), Changed<Dropdown>>,
+    mut texts: Query<&mut Text, With<ValueDisplay>>,
+) {
+    for (dropdown, children) in &dropdowns {
+        let mut texts = texts.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            text.0 = dropdown_text(dropdown);
+        }
+    }
+}
+
+fn update_spinner_text(
+    spinners: Query<(&Spinner, &Children), Changed<Spinner>>,
+    mut texts: Query<&mut Text, With<ValueDisplay>>,
+) {
+    for (spinner, children) in &spinners {
+        let mut texts = texts.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            text.0 = spinner_text(spinner.value, spinner.step);
+        }
+    }
+}
+
+fn update_text_input_text(
+    inputs: Query<
+        (&TextInput, Has<Editing>, &Children),
+        Or<(Changed<TextInput>, Changed<Editing>)>,
+    >,
+    mut removed: RemovedComponents<Editing>,
+    all_inputs: Query<(&TextInput, &Children)>,
+    mut texts: Query<&mut Text, With<ValueDisplay>>,
+) {
+    let stopped = removed
+        .read()
+        .filter_map(|entity| all_inputs.get(entity).ok())
+        .map(|(input, children)| (input, false, children));
+    for (input, editing, children) in inputs.iter().chain(stopped) {
+        let mut texts = texts.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            text.0 = text_input_text(&input.text, editing);
+        }
+    }
+}
+
+pub(super) fn toggle_color(on: bool) -> Color {
+    if on {
+        TOGGLE_ON
+    } else {
+        TOGGLE_OFF
+    }
+}
+
+pub(super) fn dropdown_text(dropdown: &Dropdown) -> String {
+    let selected = dropdown
+        .options
+        .get(dropdown.selected)
+        .map(String::as_str)
+        .unwrap_or_default();
+    format!("{selected} v")
+}
+
+/// Shows as many decimals as the step has, so that rounding errors like `0.30000001` don't show up.
+pub(super) fn spinner_text(value: f32, step: f32) -> String {
+    let decimals = (0..MAX_SPINNER_DECIMALS)
+        .find(|&decimals| {
+            let scaled = step * 10f32.powi(decimals as i32);
+            (scaled - scaled.round()).abs() < 1e-3
+        })
+        .unwrap_or(MAX_SPINNER_DECIMALS);
+    format!("{value:.decimals$}")
+}
+
+pub(super) fn text_input_text(text: &str, editing: bool) -> String {
+    if editing {
+        format!("{text}|")
+    } else {
+        text.to_string()
+    }
+}
@@ -62,9 +62,9 @@ fn apply_interaction_palette(
 #[derive(Resource, Asset, Reflect, Clone)]
 pub struct InteractionAssets {
     #[dependency]
-    hover: Handle<AudioSource>,
+    pub(super) hover: Handle<AudioSource>,
     #[dependency]
-    press: Handle<AudioSource>,
+    pub(super) press: Handle<AudioSource>,
 }
 
 impl InteractionAssets {
@@ -3,15 +3,21 @@
 // Unused utilities may trigger this lints undesirably.
 #![allow(dead_code)]
 
+pub mod inputs;
 pub mod interaction;
+pub mod navigation;
 pub mod palette;
+pub mod progress;
 mod widgets;
 
 #[allow(unused_imports)]
 pub mod prelude {
     pub use super::{
+        inputs::ValueChanged,
         interaction::{InteractionPalette, OnPress},
+        navigation::BackButton,
         palette as ui_palette,
+        progress::ProgressBar,
         widgets::{Containers as _, Widgets as _},
     };
 }
@@ -19,5 +25,10 @@ pub mod prelude {
 use bevy::prelude::*;
 
 pub(super) fn plugin(app: &mut App) {
-    app.add_plugins(interaction::plugin);
+    app.add_plugins((
+        inputs::plugin,
+        interaction::plugin,
+        navigation::plugin,
+        progress::plugin,
+    ));
 }
@@ -0,0 +1,168 @@
+//! Gamepad navigation for menus. The D-pad moves a focus ring between buttons, South presses the
+//! focused button and East presses the screen's [`BackButton`], both triggering [`OnPress`] just
+//! like a mouse click would.
+
+use bevy::{prelude::*, ui::Val::*};
+
+use crate::{
+    audio::SoundEffect,
+    theme::{
+        interaction::{InteractionAssets, OnPress},
+        palette::FOCUS_RING,
+    },
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Focused>();
+    app.register_type::<BackButton>();
+    app.add_systems(
+        Update,
+        (move_focus, press_focused, press_back, draw_focus_ring)
+            .chain()
+            .in_set(NavigationSet)
+            .run_if(resource_exists::<InteractionAssets>),
+    );
+}
+
+/// Systems handling gamepad menu navigation. Configure this set to disable navigation where the
+/// gamepad is used for something else.
+#[derive(SystemSet, Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct NavigationSet;
+
+/// Marks the button that has gamepad focus.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct Focused;
+
+/// Marks the button that the gamepad's back button should press, e.g. "Back" on the credits screen.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct BackButton;
+
+const NAVIGATION_BUTTONS: [(GamepadButton, Vec2); 4] = [
+    // UI coordinates grow downwards.
+    (GamepadButton::DPadUp, Vec2::NEG_Y),
+    (GamepadButton::DPadDown, Vec2::Y),
+    (GamepadButton::DPadLeft, Vec2::NEG_X),
+    (GamepadButton::DPadRight, Vec2::X),
+];
+
+fn move_focus(
+    mut commands: Commands,
+    gamepads: Query<&Gamepad>,
+    buttons: Query<(Entity, &GlobalTransform, &InheritedVisibility), With<Button>>,
+    focused: Query<Entity, With<Focused>>,
+    interaction_assets: Res<InteractionAssets>,
+) {
+    let Some(direction) = gamepads.iter().find_map(|gamepad| {
+        NAVIGATION_BUTTONS
+            .into_iter()
+            .find(|(button, _)| gamepad.just_pressed(*button))
+            .map(|(_, direction)| direction)
+    }) else {
+        return;
+    };
+
+    let visible = buttons
+        .iter()
+        .filter(|(_, _, visibility)| visibility.get())
+        .map(|(entity, transform, _)| (entity, transform.translation().truncate()));
+    let current = focused
+        .get_single()
+        .ok()
+        .and_then(|entity| buttons.get(entity).ok())
+        .map(|(entity, transform, _)| (entity, transform.translation().truncate()));
+
+    let next = match current {
+        // Pick the closest button in the pressed direction, preferring ones that are well aligned.
+        Some((current, position)) => visible
+            .filter(|(entity, _)| *entity != current)
+            .filter_map(|(entity, other)| {
+                let offset = other - position;
+                let along = offset.dot(direction);
+                (along > 0.0).then(|| (entity, along + 2.0 * offset.perp_dot(direction).abs()))
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(entity, _)| entity),
+        // Nothing is focused yet, so start with the top-left button.
+        None => visible
+            .min_by(|(_, a), (_, b)| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)))
+            .map(|(entity, _)| entity),
+    };
+    let Some(next) = next else {
+        return;
+    };
+
+    for entity in &focused {
+        commands.entity(entity).remove::<Focused>();
+    }
+    commands.entity(next).insert(Focused);
+    commands.spawn((
+        AudioPlayer(interaction_assets.hover.clone()),
+        PlaybackSettings::DESPAWN,
+        SoundEffect,
+    ));
+}
+
+fn press_focused(
+    mut commands: Commands,
+    gamepads: Query<&Gamepad>,
+    focused: Query<Entity, With<Focused>>,
+    interaction_assets: Res<InteractionAssets>,
+) {
+    if !gamepads
+        .iter()
+        .any(|gamepad| gamepad.just_pressed(GamepadButton::South))
+    {
+        return;
+    }
+    for entity in &focused {
+        press(&mut commands, entity, &interaction_assets);
+    }
+}
+
+fn press_back(
+    mut commands: Commands,
+    gamepads: Query<&Gamepad>,
+    back_buttons: Query<(Entity, &InheritedVisibility), With<BackButton>>,
+    interaction_assets: Res<InteractionAssets>,
+) {
+    if !gamepads
+        .iter()
+        .any(|gamepad| gamepad.just_pressed(GamepadButton::East))
+    {
+        return;
+    }
+    for (entity, visibility) in &back_buttons {
+        if visibility.get() {
+            press(&mut commands, entity, &interaction_assets);
+        }
+    }
+}
+
+fn press(commands: &mut Commands, entity: Entity, interaction_assets: &InteractionAssets) {
+    commands.trigger_targets(OnPress, entity);
+    commands.spawn((
+        AudioPlayer(interaction_assets.press.clone()),
+        PlaybackSettings::DESPAWN,
+        SoundEffect,
+    ));
+}
+
+fn draw_focus_ring(
+    mut commands: Commands,
+    added: Query<Entity, Added<Focused>>,
+    mut removed: RemovedComponents<Focused>,
+    mut outlines: Query<&mut Outline>,
+) {
+    for entity in removed.read() {
+        if let Ok(mut outline) = outlines.get_mut(entity) {
+            outline.color = Color::NONE;
+        }
+    }
+    for entity in &added {
+        commands
+            .entity(entity)
+            .insert(Outline::new(Px(3.0), Px(2.0), FOCUS_RING));
+    }
+}
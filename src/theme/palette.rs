@@ -6,5 +6,19 @@ pub const BUTTON_PRESSED_BACKGROUND: Color = Color::srgb(0.286, 0.478, 0.773);
 pub const BUTTON_TEXT: Color = Color::srgb(0.925, 0.925, 0.925);
 pub const LABEL_TEXT: Color = Color::srgb(0.867, 0.827, 0.412);
 pub const HEADER_TEXT: Color = Color::srgb(0.867, 0.827, 0.412);
+pub const ERROR_TEXT: Color = Color::srgb(0.937, 0.325, 0.314);
 
 pub const NODE_BACKGROUND: Color = Color::srgb(0.286, 0.478, 0.773);
+
+pub const SELECTED_OUTLINE: Color = Color::srgb(0.925, 0.925, 0.925);
+pub const FOCUS_RING: Color = Color::srgb(0.867, 0.827, 0.412);
+
+pub const SLIDER_TRACK: Color = Color::srgb(0.157, 0.157, 0.157);
+pub const SLIDER_FILL: Color = Color::srgb(0.867, 0.827, 0.412);
+pub const TOGGLE_ON: Color = Color::srgb(0.867, 0.827, 0.412);
+pub const TOGGLE_OFF: Color = Color::srgb(0.157, 0.157, 0.157);
+pub const TEXT_INPUT_BACKGROUND: Color = Color::srgb(0.157, 0.157, 0.157);
+pub const PROGRESS_BAR_BACKGROUND: Color = Color::srgb(0.157, 0.157, 0.157);
+pub const PROGRESS_BAR_FILL: Color = Color::srgb(0.286, 0.478, 0.773);
+
+pub const OVERLAY_BACKGROUND: Color = Color::srgba(0.0, 0.0, 0.0, 0.6);
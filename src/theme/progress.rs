@@ -0,0 +1,29 @@
+//! Progress bars spawned with [`Widgets::progress_bar`](super::widgets::Widgets::progress_bar).
+
+use bevy::{prelude::*, ui::Val::*};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<ProgressBar>();
+    app.add_systems(Update, update_progress_bar_fill);
+}
+
+/// How full a progress bar is, between 0 and 1. Set this to update the bar.
+#[derive(Component, Debug, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct ProgressBar(pub f32);
+
+/// Marks the child node that fills a [`ProgressBar`].
+#[derive(Component, Debug)]
+pub(super) struct ProgressBarFill;
+
+fn update_progress_bar_fill(
+    progress_bars: Query<(&ProgressBar, &Children), Changed<ProgressBar>>,
+    mut fills: Query<&mut Node, With<ProgressBarFill>>,
+) {
+    for (progress_bar, children) in &progress_bars {
+        let mut fills = fills.iter_many_mut(children);
+        while let Some(mut fill) = fills.fetch_next() {
+            fill.width = Percent(progress_bar.0.clamp(0.0, 1.0) * 100.0);
+        }
+    }
+}
@@ -1,19 +1,54 @@
 //! Helper traits for creating common widgets.
 
-use bevy::{ecs::system::EntityCommands, hierarchy::ChildBuild, prelude::*, ui::Val::*};
+use bevy::{
+    ecs::system::EntityCommands,
+    hierarchy::ChildBuild,
+    prelude::*,
+    ui::{FocusPolicy, RelativeCursorPosition, Val::*},
+};
 
-use crate::theme::{interaction::InteractionPalette, palette::*};
+use crate::theme::{
+    inputs::{self, Dropdown, Slider, Spinner, TextInput, Toggle, ValueDisplay},
+    interaction::InteractionPalette,
+    palette::*,
+    progress::{ProgressBar, ProgressBarFill},
+};
 
 /// An extension trait for spawning UI widgets.
 pub trait Widgets {
     /// Spawn a simple button with text.
     fn button(&mut self, text: impl Into<String>) -> EntityCommands;
 
+    /// Spawn a compact button with smaller text that fits into toolbars.
+    fn small_button(&mut self, text: impl Into<String>) -> EntityCommands;
+
     /// Spawn a simple header label. Bigger than [`Widgets::label`].
     fn header(&mut self, text: impl Into<String>) -> EntityCommands;
 
     /// Spawn a simple text label.
     fn label(&mut self, text: impl Into<String>) -> EntityCommands;
+
+    /// Spawn a horizontal slider. Dragging it triggers [`ValueChanged<f32>`](inputs::ValueChanged).
+    fn slider(&mut self, value: f32, min: f32, max: f32, step: f32) -> EntityCommands;
+
+    /// Spawn a checkbox. Pressing it triggers [`ValueChanged<bool>`](inputs::ValueChanged).
+    fn toggle(&mut self, on: bool) -> EntityCommands;
+
+    /// Spawn a dropdown list. Picking an option triggers
+    /// [`ValueChanged<usize>`](inputs::ValueChanged) with its index.
+    fn dropdown(&mut self, options: Vec<String>, selected: usize) -> EntityCommands;
+
+    /// Spawn a number with buttons to step it down and up. Stepping it triggers
+    /// [`ValueChanged<f32>`](inputs::ValueChanged).
+    fn spinner(&mut self, value: f32, min: f32, max: f32, step: f32) -> EntityCommands;
+
+    /// Spawn a single-line text field. Typing into it triggers
+    /// [`ValueChanged<String>`](inputs::ValueChanged).
+    fn text_input(&mut self, text: impl Into<String>, max_length: usize) -> EntityCommands;
+
+    /// Spawn a horizontal bar filled up to `fraction`, which is between 0 and 1.
+    /// Update it through its [`ProgressBar`] component.
+    fn progress_bar(&mut self, fraction: f32) -> EntityCommands;
 }
 
 impl<T: Spawn> Widgets for T {
@@ -53,6 +88,43 @@ impl<T: Spawn> Widgets for T {
         entity
     }
 
+    fn small_button(&mut self, text: impl Into<String>) -> EntityCommands {
+        let mut entity = self.spawn((
+            Name::new("Small Button"),
+            Button,
+            Node {
+                min_width: Px(80.0),
+                height: Px(36.0),
+                padding: UiRect::horizontal(Px(10.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(NODE_BACKGROUND),
+            InteractionPalette {
+                none: NODE_BACKGROUND,
+                hovered: BUTTON_HOVERED_BACKGROUND,
+                pressed: BUTTON_PRESSED_BACKGROUND,
+            },
+        ));
+        entity.with_children(|children| {
+            ChildBuild::spawn(
+                children,
+                (
+                    Name::new("Button Text"),
+                    Text(text.into()),
+                    TextFont {
+                        font_size: 20.0,
+                        ..default()
+                    },
+                    TextColor(BUTTON_TEXT),
+                ),
+            );
+        });
+
+        entity
+    }
+
     fn header(&mut self, text: impl Into<String>) -> EntityCommands {
         let mut entity = self.spawn((
             Name::new("Header"),
@@ -98,6 +170,247 @@ impl<T: Spawn> Widgets for T {
         ));
         entity
     }
+
+    fn slider(&mut self, value: f32, min: f32, max: f32, step: f32) -> EntityCommands {
+        let slider = Slider {
+            value,
+            min,
+            max,
+            step,
+        };
+        let fraction = slider.fraction();
+        let mut entity = self.spawn((
+            Name::new("Slider"),
+            slider,
+            Button,
+            RelativeCursorPosition::default(),
+            Node {
+                width: Px(240.0),
+                height: Px(20.0),
+                ..default()
+            },
+            BackgroundColor(SLIDER_TRACK),
+            InteractionPalette {
+                none: SLIDER_TRACK,
+                hovered: BUTTON_HOVERED_BACKGROUND,
+                pressed: BUTTON_PRESSED_BACKGROUND,
+            },
+        ));
+        entity.with_children(|children| {
+            ChildBuild::spawn(
+                children,
+                (
+                    Name::new("Slider Fill"),
+                    ValueDisplay,
+                    Node {
+                        width: Percent(fraction * 100.0),
+                        height: Percent(100.0),
+                        ..default()
+                    },
+                    BackgroundColor(SLIDER_FILL),
+                ),
+            );
+        });
+
+        entity
+    }
+
+    fn toggle(&mut self, on: bool) -> EntityCommands {
+        let mut entity = self.spawn((
+            Name::new("Toggle"),
+            Toggle(on),
+            Button,
+            Node {
+                width: Px(36.0),
+                height: Px(36.0),
+                padding: UiRect::all(Px(6.0)),
+                ..default()
+            },
+            BackgroundColor(NODE_BACKGROUND),
+            InteractionPalette {
+                none: NODE_BACKGROUND,
+                hovered: BUTTON_HOVERED_BACKGROUND,
+                pressed: BUTTON_PRESSED_BACKGROUND,
+            },
+        ));
+        entity.with_children(|children| {
+            ChildBuild::spawn(
+                children,
+                (
+                    Name::new("Toggle Indicator"),
+                    ValueDisplay,
+                    Node {
+                        width: Percent(100.0),
+                        height: Percent(100.0),
+                        ..default()
+                    },
+                    BackgroundColor(inputs::toggle_color(on)),
+                ),
+            );
+        });
+        entity.observe(inputs::toggle_on_press);
+
+        entity
+    }
+
+    fn dropdown(&mut self, options: Vec<String>, selected: usize) -> EntityCommands {
+        let dropdown = Dropdown::new(options, selected);
+        let text = inputs::dropdown_text(&dropdown);
+        let mut entity = self.spawn((
+            Name::new("Dropdown"),
+            dropdown,
+            Button,
+            Node {
+                min_width: Px(200.0),
+                height: Px(36.0),
+                padding: UiRect::horizontal(Px(10.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(NODE_BACKGROUND),
+            InteractionPalette {
+                none: NODE_BACKGROUND,
+                hovered: BUTTON_HOVERED_BACKGROUND,
+                pressed: BUTTON_PRESSED_BACKGROUND,
+            },
+        ));
+        entity.with_children(|children| {
+            ChildBuild::spawn(
+                children,
+                (
+                    Name::new("Dropdown Text"),
+                    ValueDisplay,
+                    Text(text),
+                    TextFont {
+                        font_size: 20.0,
+                        ..default()
+                    },
+                    TextColor(BUTTON_TEXT),
+                ),
+            );
+        });
+        entity.observe(inputs::open_dropdown_on_press);
+
+        entity
+    }
+
+    fn spinner(&mut self, value: f32, min: f32, max: f32, step: f32) -> EntityCommands {
+        let mut entity = self.spawn((
+            Name::new("Spinner"),
+            Spinner {
+                value,
+                min,
+                max,
+                step,
+            },
+            Node {
+                align_items: AlignItems::Center,
+                column_gap: Px(10.0),
+                ..default()
+            },
+        ));
+        let spinner = entity.id();
+        entity.with_children(|children| {
+            children
+                .small_button("-")
+                .observe(inputs::step_spinner(spinner, -1.0));
+            ChildBuild::spawn(
+                children,
+                (
+                    Name::new("Spinner Value"),
+                    ValueDisplay,
+                    Text(inputs::spinner_text(value, step)),
+                    TextFont {
+                        font_size: 24.0,
+                        ..default()
+                    },
+                    TextColor(LABEL_TEXT),
+                    Node {
+                        min_width: Px(60.0),
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    TextLayout::new_with_justify(JustifyText::Center),
+                ),
+            );
+            children
+                .small_button("+")
+                .observe(inputs::step_spinner(spinner, 1.0));
+        });
+
+        entity
+    }
+
+    fn text_input(&mut self, text: impl Into<String>, max_length: usize) -> EntityCommands {
+        let text = text.into();
+        let mut entity = self.spawn((
+            Name::new("Text Input"),
+            Button,
+            Node {
+                width: Px(300.0),
+                height: Px(36.0),
+                padding: UiRect::horizontal(Px(10.0)),
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(TEXT_INPUT_BACKGROUND),
+            InteractionPalette {
+                none: TEXT_INPUT_BACKGROUND,
+                hovered: BUTTON_HOVERED_BACKGROUND,
+                pressed: BUTTON_PRESSED_BACKGROUND,
+            },
+        ));
+        entity.with_children(|children| {
+            ChildBuild::spawn(
+                children,
+                (
+                    Name::new("Text Input Text"),
+                    ValueDisplay,
+                    Text(inputs::text_input_text(&text, false)),
+                    TextFont {
+                        font_size: 20.0,
+                        ..default()
+                    },
+                    TextColor(BUTTON_TEXT),
+                ),
+            );
+        });
+        entity.insert(TextInput { text, max_length });
+        entity.observe(inputs::edit_text_on_press);
+
+        entity
+    }
+
+    fn progress_bar(&mut self, fraction: f32) -> EntityCommands {
+        let mut entity = self.spawn((
+            Name::new("Progress Bar"),
+            ProgressBar(fraction),
+            Node {
+                width: Px(500.0),
+                height: Px(24.0),
+                ..default()
+            },
+            BackgroundColor(PROGRESS_BAR_BACKGROUND),
+        ));
+        entity.with_children(|children| {
+            ChildBuild::spawn(
+                children,
+                (
+                    Name::new("Progress Bar Fill"),
+                    ProgressBarFill,
+                    Node {
+                        width: Percent(fraction.clamp(0.0, 1.0) * 100.0),
+                        height: Percent(100.0),
+                        ..default()
+                    },
+                    BackgroundColor(PROGRESS_BAR_FILL),
+                ),
+            );
+        });
+
+        entity
+    }
 }
 
 /// An extension trait for spawning UI containers.
@@ -105,6 +418,9 @@ pub trait Containers {
     /// Spawns a root node that covers the full screen
     /// and centers its content horizontally and vertically.
     fn ui_root(&mut self) -> EntityCommands;
+
+    /// Spawns a [`Containers::ui_root`] that dims and blocks clicks to everything beneath it.
+    fn overlay_root(&mut self) -> EntityCommands;
 }
 
 impl Containers for Commands<'_, '_> {
@@ -123,6 +439,17 @@ impl Containers for Commands<'_, '_> {
             },
         ))
     }
+
+    fn overlay_root(&mut self) -> EntityCommands {
+        let mut entity = self.ui_root();
+        entity.insert((
+            Name::new("Overlay Root"),
+            BackgroundColor(OVERLAY_BACKGROUND),
+            FocusPolicy::Block,
+            GlobalZIndex(1),
+        ));
+        entity
+    }
 }
 
 /// An internal trait for types that can spawn entities.